@@ -0,0 +1,185 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    input_utils::{self, InputError, LineError},
+    solution::{Answer, Part},
+};
+
+/// The accepted answers for our own puzzle inputs, so refactors can be checked against them.
+///
+/// The file is a small subset of TOML, one table per day:
+///
+/// ```toml
+/// [day1]
+/// part1 = 1016964
+/// part2 = "text answers are quoted"
+/// ```
+#[derive(Debug, Default)]
+pub struct KnownAnswers {
+    answers: HashMap<(u8, Part), String>,
+}
+
+/// The outcome of checking one part's answer against the known answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// there is no known answer to check against
+    Missing,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => f.write_str("pass"),
+            Verdict::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+            Verdict::Missing => f.write_str("missing"),
+        }
+    }
+}
+
+impl KnownAnswers {
+    pub fn load(file_name: &str) -> Result<Self, InputError> {
+        Self::parse(&input_utils::try_read_all(file_name)?).map_err(|e| e.in_file(file_name))
+    }
+
+    pub fn parse(lines: &[String]) -> Result<Self, LineError> {
+        let mut answers = HashMap::new();
+        let mut current_day = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_error = |message: &str| LineError::new(i, line, message);
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = trimmed.strip_prefix('[') {
+                let day = table
+                    .strip_suffix(']')
+                    .and_then(|table| table.trim().strip_prefix("day"))
+                    .and_then(|day| day.parse::<u8>().ok())
+                    .ok_or_else(|| line_error("expected a table like [day1]"))?;
+                current_day = Some(day);
+                continue;
+            }
+
+            let day =
+                current_day.ok_or_else(|| line_error("answer is not inside a [dayN] table"))?;
+
+            let (key, value) = match trimmed.find('=') {
+                Some(i) => (trimmed[..i].trim(), trimmed[(i + 1)..].trim()),
+                None => return Err(line_error("expected part1 = ... or part2 = ...")),
+            };
+
+            let part = match key {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                _ => return Err(line_error("expected part1 = ... or part2 = ...")),
+            };
+
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .ok_or_else(|| line_error("unterminated string"))?,
+                None if value.parse::<i64>().is_ok() => value,
+                None => return Err(line_error("expected a number or a quoted string")),
+            };
+
+            if answers.insert((day, part), value.to_string()).is_some() {
+                return Err(line_error("answer is already defined"));
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    pub fn check(&self, day: u8, part: Part, answer: &Answer) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Missing,
+            Some(expected) if *answer != Answer::Unsolved && answer.to_string() == expected => {
+                Verdict::Pass
+            }
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KnownAnswers, Verdict};
+    use crate::solution::{Answer, Part};
+
+    fn parse(input: &str) -> KnownAnswers {
+        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+        KnownAnswers::parse(&lines).unwrap()
+    }
+
+    #[test]
+    fn parse_happy() {
+        let answers = parse(
+            "# our answers
+[day1]
+part1 = 514579
+part2 = \"abc\"
+
+[day2]
+part1 = -3",
+        );
+
+        assert_eq!(answers.get(1, Part::One), Some("514579"));
+        assert_eq!(answers.get(1, Part::Two), Some("abc"));
+        assert_eq!(answers.get(2, Part::One), Some("-3"));
+        assert_eq!(answers.get(2, Part::Two), None);
+    }
+
+    #[test]
+    fn parse_errors_have_line_numbers() {
+        for (input, line_number) in [
+            ("part1 = 3", 1),
+            ("[day1]\npart3 = 3", 2),
+            ("[day1]\npart1 = abc", 2),
+            ("[day1]\npart1 = 1\npart1 = 2", 3),
+            ("[dayone]", 1),
+        ] {
+            let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+            let error = KnownAnswers::parse(&lines).unwrap_err();
+            assert_eq!(error.line_number, line_number, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn check_verdicts() {
+        let answers = parse("[day1]\npart1 = 7\npart2 = 8");
+
+        assert_eq!(
+            answers.check(1, Part::One, &Answer::Number(7)),
+            Verdict::Pass
+        );
+        assert_eq!(
+            answers.check(1, Part::Two, &Answer::Number(9)),
+            Verdict::Fail {
+                expected: "8".to_string()
+            }
+        );
+        assert_eq!(
+            answers.check(1, Part::Two, &Answer::Unsolved),
+            Verdict::Fail {
+                expected: "8".to_string()
+            }
+        );
+        assert_eq!(
+            answers.check(2, Part::One, &Answer::Number(1)),
+            Verdict::Missing
+        );
+    }
+}
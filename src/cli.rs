@@ -0,0 +1,656 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use adventofcode2020::{day3::Slope, solution::Part};
+
+pub const USAGE: &str = "usage: adventofcode2020 [COMMAND] [--day N]... [--days A-B] [--all] [--part 1|2] [--input PATH]
+
+commands:
+  run           print the answers (the default)
+  time          measure how long parsing and each part take
+  verify        check the answers against the known answers file
+  audit         list every day 2 password with its verdict under each policy
+  path          draw the day 3 toboggan path over the repeated map
+  passports     list every day 4 passport with the fields that failed validation
+  bags          write the day 7 bag rules as a Graphviz DOT file
+  help          show this message
+
+options:
+  --day N       run a single day (can be repeated)
+  --days A-B    run an inclusive range of days, e.g. 3-8
+  --all         run every day (the default when no day is given)
+  --part 1|2    only run the given part
+  --input PATH  read the puzzle input from PATH instead of inputs/dayN
+                (only valid when a single day is selected)
+  --format F    how time and audit write their report: table (default), json or csv
+  --answers F   the known answers file used by verify (default answers.toml)
+  --min-distinct N
+                audit also requires passwords to have N different characters
+  --right N     how far right path moves each step (default 3)
+  --down N      how far down path moves each step (default 1)
+  --tiles N     how many copies of the map path draws side by side at least
+  --colour      colour the squares path visits
+  --schema F    the schema passports checks against (default: the day 4 part 2 rules)
+                --part 1 only checks the required fields are there
+  --export F    instead of the report, passports writes every valid passport as jsonl or csv
+  --highlight C bags fills in colour C and every bag that holds it or is inside it
+  --output PATH where bags writes the DOT file (default bags.dot)";
+
+pub const DEFAULT_ANSWERS_PATH: &str = "answers.toml";
+pub const DEFAULT_DOT_PATH: &str = "bags.dot";
+
+pub const FIRST_DAY: u8 = 1;
+pub const LAST_DAY: u8 = 25;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(RunOptions),
+    Time(RunOptions, Format),
+    /// check the answers against the known answers file at the given path
+    Verify(RunOptions, String),
+    Audit(AuditOptions),
+    Path(PathOptions),
+    Passports(PassportsOptions),
+    Bags(BagsOptions),
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunOptions {
+    /// the days to run, in ascending order. Empty means every day.
+    pub days: Vec<u8>,
+    pub part: Option<Part>,
+    pub input: Option<String>,
+}
+
+impl RunOptions {
+    /// the parts to run, in order
+    pub fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => vec![Part::One, Part::Two],
+        }
+    }
+}
+
+/// options for auditing the day 2 password database
+#[derive(Debug, PartialEq, Eq)]
+pub struct AuditOptions {
+    pub input: Option<String>,
+    pub format: Format,
+    /// also check every password has at least this many different characters
+    pub min_distinct_chars: Option<usize>,
+}
+
+/// options for drawing the day 3 toboggan path
+#[derive(Debug, PartialEq, Eq)]
+pub struct PathOptions {
+    pub input: Option<String>,
+    pub slope: Slope,
+    pub tiles: Option<usize>,
+    pub colour: bool,
+}
+
+/// options for checking the day 4 passports
+#[derive(Debug, PartialEq, Eq)]
+pub struct PassportsOptions {
+    pub input: Option<String>,
+    /// the schema file to use instead of the built in one
+    pub schema: Option<String>,
+    /// check every value follows its rule, not just that the required fields are there
+    pub strict: bool,
+    /// write out the valid passports instead of the report
+    pub export: Option<Export>,
+}
+
+/// options for exporting the day 7 bag rules
+#[derive(Debug, PartialEq, Eq)]
+pub struct BagsOptions {
+    pub input: Option<String>,
+    /// the colour to highlight along with its ancestors and descendants, e.g. "shiny gold"
+    pub highlight: Option<String>,
+    pub output: String,
+}
+
+/// how cleaned up records are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    /// one JSON object per line
+    JsonLines,
+    Csv,
+}
+
+impl FromStr for Export {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(Export::JsonLines),
+            "csv" => Ok(Export::Csv),
+            other => arg_error(format!(
+                "invalid export format {:?}, expected jsonl or csv",
+                other
+            )),
+        }
+    }
+}
+
+/// how reports are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => arg_error(format!(
+                "invalid format {:?}, expected one of table, json or csv",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArgError(String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+fn arg_error<T>(message: impl Into<String>) -> Result<T, ArgError> {
+    Err(ArgError(message.into()))
+}
+
+pub fn parse_args<I>(args: I) -> Result<Command, ArgError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+
+    let command = match args.peek().map(|s| s.as_str()) {
+        Some("help") | Some("-h") | Some("--help") => return Ok(Command::Help),
+        Some(name) if !name.starts_with("--") => args.next().unwrap(),
+        _ => String::from("run"),
+    };
+
+    let allowed_flags = match command.as_str() {
+        "run" => DAY_FLAGS.to_vec(),
+        "time" => [DAY_FLAGS, &["--format"]].concat(),
+        "verify" => [DAY_FLAGS, &["--answers"]].concat(),
+        "audit" => vec!["--input", "--format", "--min-distinct"],
+        "path" => vec!["--input", "--right", "--down", "--tiles", "--colour"],
+        "passports" => vec!["--input", "--schema", "--part", "--export"],
+        "bags" => vec!["--input", "--highlight", "--output"],
+        other => return arg_error(format!("unknown command {:?}", other)),
+    };
+
+    let flags = match parse_flags(args)? {
+        Some(flags) => flags,
+        None => return Ok(Command::Help),
+    };
+
+    if let Some(flag) = flags.seen.iter().find(|flag| !allowed_flags.contains(flag)) {
+        return arg_error(format!(
+            "{} is not supported by the {} command",
+            flag, command
+        ));
+    }
+
+    let options = flags.options;
+    if allowed_flags.contains(&"--day") && options.input.is_some() && options.days.len() != 1 {
+        return arg_error("--input requires exactly one selected day");
+    }
+
+    match command.as_str() {
+        "run" => Ok(Command::Run(options)),
+        "time" => Ok(Command::Time(
+            options,
+            flags.format.unwrap_or(Format::Table),
+        )),
+        "verify" => Ok(Command::Verify(
+            options,
+            flags
+                .answers
+                .unwrap_or_else(|| String::from(DEFAULT_ANSWERS_PATH)),
+        )),
+        "audit" => Ok(Command::Audit(AuditOptions {
+            input: options.input,
+            format: flags.format.unwrap_or(Format::Table),
+            min_distinct_chars: flags.min_distinct_chars,
+        })),
+        "path" => Ok(Command::Path(PathOptions {
+            input: options.input,
+            slope: Slope {
+                right: flags.right.unwrap_or(3),
+                down: flags.down.unwrap_or(1),
+            },
+            tiles: flags.tiles,
+            colour: flags.colour,
+        })),
+        "passports" => {
+            // exported passports are typed, so they always follow the built in part 2 rules
+            if flags.export.is_some() && (flags.schema.is_some() || options.part.is_some()) {
+                return arg_error("--export cannot be combined with --schema or --part");
+            }
+
+            Ok(Command::Passports(PassportsOptions {
+                input: options.input,
+                schema: flags.schema,
+                strict: options.part != Some(Part::One),
+                export: flags.export,
+            }))
+        }
+        "bags" => Ok(Command::Bags(BagsOptions {
+            input: options.input,
+            highlight: flags.highlight,
+            output: flags
+                .output
+                .unwrap_or_else(|| String::from(DEFAULT_DOT_PATH)),
+        })),
+        _ => unreachable!(),
+    }
+}
+
+/// the flags that select which days and parts to run
+const DAY_FLAGS: &[&str] = &["--day", "--days", "--all", "--part", "--input"];
+
+/// every flag given, before checking they make sense for the command
+struct Flags {
+    options: RunOptions,
+    format: Option<Format>,
+    answers: Option<String>,
+    min_distinct_chars: Option<usize>,
+    right: Option<usize>,
+    down: Option<usize>,
+    tiles: Option<usize>,
+    colour: bool,
+    schema: Option<String>,
+    export: Option<Export>,
+    highlight: Option<String>,
+    output: Option<String>,
+    /// the name of every flag that was given
+    seen: Vec<&'static str>,
+}
+
+/// returns `None` if help was asked for
+fn parse_flags<I>(mut args: I) -> Result<Option<Flags>, ArgError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut days = Vec::new();
+    let mut all = false;
+    let mut part = None;
+    let mut input = None;
+    let mut format = None;
+    let mut answers = None;
+    let mut min_distinct_chars = None;
+    let mut right = None;
+    let mut down = None;
+    let mut tiles = None;
+    let mut colour = false;
+    let mut schema = None;
+    let mut export = None;
+    let mut highlight = None;
+    let mut output = None;
+    let mut seen = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .map_or_else(|| arg_error(format!("{} expects a value", flag)), Ok)
+        };
+
+        let flag = match arg.as_str() {
+            "--day" => {
+                days.push(parse_day(&value_for("--day")?)?);
+                "--day"
+            }
+            "--days" => {
+                days.extend(parse_day_range(&value_for("--days")?)?);
+                "--days"
+            }
+            "--all" => {
+                all = true;
+                "--all"
+            }
+            "--part" => {
+                part = Some(match value_for("--part")?.as_str() {
+                    "1" => Part::One,
+                    "2" => Part::Two,
+                    other => {
+                        return arg_error(format!("invalid part {:?}, expected 1 or 2", other))
+                    }
+                });
+                "--part"
+            }
+            "--input" => {
+                input = Some(value_for("--input")?);
+                "--input"
+            }
+            "--format" => {
+                format = Some(value_for("--format")?.parse::<Format>()?);
+                "--format"
+            }
+            "--answers" => {
+                answers = Some(value_for("--answers")?);
+                "--answers"
+            }
+            "--min-distinct" => {
+                min_distinct_chars = Some(parse_count(
+                    "--min-distinct",
+                    &value_for("--min-distinct")?,
+                )?);
+                "--min-distinct"
+            }
+            "--right" => {
+                right = Some(parse_count("--right", &value_for("--right")?)?);
+                "--right"
+            }
+            "--down" => {
+                let value = parse_count("--down", &value_for("--down")?)?;
+                if value == 0 {
+                    return arg_error("--down must be at least 1");
+                }
+                down = Some(value);
+                "--down"
+            }
+            "--tiles" => {
+                tiles = Some(parse_count("--tiles", &value_for("--tiles")?)?);
+                "--tiles"
+            }
+            "--colour" => {
+                colour = true;
+                "--colour"
+            }
+            "--schema" => {
+                schema = Some(value_for("--schema")?);
+                "--schema"
+            }
+            "--export" => {
+                export = Some(value_for("--export")?.parse::<Export>()?);
+                "--export"
+            }
+            "--highlight" => {
+                highlight = Some(value_for("--highlight")?);
+                "--highlight"
+            }
+            "--output" => {
+                output = Some(value_for("--output")?);
+                "--output"
+            }
+            "-h" | "--help" => return Ok(None),
+            other => return arg_error(format!("unknown argument {:?}", other)),
+        };
+        seen.push(flag);
+    }
+
+    if all && !days.is_empty() {
+        return arg_error("--all cannot be combined with --day or --days");
+    }
+
+    days.sort_unstable();
+    days.dedup();
+
+    Ok(Some(Flags {
+        options: RunOptions { days, part, input },
+        format,
+        answers,
+        min_distinct_chars,
+        right,
+        down,
+        tiles,
+        colour,
+        schema,
+        export,
+        highlight,
+        output,
+        seen,
+    }))
+}
+
+fn parse_count(flag: &str, value: &str) -> Result<usize, ArgError> {
+    value
+        .parse::<usize>()
+        .or_else(|_| arg_error(format!("invalid {} {:?}, expected a number", flag, value)))
+}
+
+fn parse_day(s: &str) -> Result<u8, ArgError> {
+    match s.trim().parse::<u8>() {
+        Ok(day) if (FIRST_DAY..=LAST_DAY).contains(&day) => Ok(day),
+        _ => arg_error(format!(
+            "invalid day {:?}, expected a number from {} to {}",
+            s, FIRST_DAY, LAST_DAY
+        )),
+    }
+}
+
+fn parse_day_range(s: &str) -> Result<RangeInclusive<u8>, ArgError> {
+    let (start, end) = match s.find('-') {
+        Some(i) => (parse_day(&s[..i])?, parse_day(&s[(i + 1)..])?),
+        None => {
+            let day = parse_day(s)?;
+            (day, day)
+        }
+    };
+
+    if start > end {
+        return arg_error(format!("invalid day range {:?}, start is after end", s));
+    }
+
+    Ok(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_args, AuditOptions, BagsOptions, Command, Export, Format, Part, PassportsOptions,
+        PathOptions, RunOptions, Slope,
+    };
+
+    fn parse(args: &str) -> Result<Command, super::ArgError> {
+        parse_args(args.split_whitespace().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn no_args_runs_everything() {
+        assert_eq!(
+            parse("").unwrap(),
+            Command::Run(RunOptions {
+                days: vec![],
+                part: None,
+                input: None,
+            })
+        );
+    }
+
+    #[test]
+    fn single_day_and_part() {
+        assert_eq!(
+            parse("run --day 11 --part 2").unwrap(),
+            Command::Run(RunOptions {
+                days: vec![11],
+                part: Some(Part::Two),
+                input: None,
+            })
+        );
+    }
+
+    #[test]
+    fn day_ranges_are_merged() {
+        assert_eq!(
+            parse("run --days 3-5 --day 4 --day 9").unwrap(),
+            Command::Run(RunOptions {
+                days: vec![3, 4, 5, 9],
+                part: None,
+                input: None,
+            })
+        );
+    }
+
+    #[test]
+    fn input_override_requires_one_day() {
+        assert!(parse("run --input foo").is_err());
+        assert!(parse("run --days 1-2 --input foo").is_err());
+        assert!(parse("run --day 2 --input foo").is_ok());
+    }
+
+    #[test]
+    fn time_with_format() {
+        assert_eq!(
+            parse("time --days 1-2 --format csv").unwrap(),
+            Command::Time(
+                RunOptions {
+                    days: vec![1, 2],
+                    part: None,
+                    input: None,
+                },
+                Format::Csv
+            )
+        );
+
+        match parse("time").unwrap() {
+            Command::Time(_, format) => assert_eq!(format, Format::Table),
+            other => panic!("expected the time command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_defaults_to_the_checked_in_answers() {
+        match parse("verify --day 4").unwrap() {
+            Command::Verify(options, answers) => {
+                assert_eq!(options.days, vec![4]);
+                assert_eq!(answers, "answers.toml");
+            }
+            other => panic!("expected the verify command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn audit_takes_its_own_flags() {
+        assert_eq!(
+            parse("audit --input db.txt --format json --min-distinct 3").unwrap(),
+            Command::Audit(AuditOptions {
+                input: Some(String::from("db.txt")),
+                format: Format::Json,
+                min_distinct_chars: Some(3),
+            })
+        );
+        assert!(parse("audit --day 2").is_err());
+        assert!(parse("run --min-distinct 3").is_err());
+    }
+
+    #[test]
+    fn path_defaults_to_the_part_1_slope() {
+        assert_eq!(
+            parse("path").unwrap(),
+            Command::Path(PathOptions {
+                input: None,
+                slope: Slope { right: 3, down: 1 },
+                tiles: None,
+                colour: false,
+            })
+        );
+        assert_eq!(
+            parse("path --right 1 --down 2 --tiles 4 --colour").unwrap(),
+            Command::Path(PathOptions {
+                input: None,
+                slope: Slope { right: 1, down: 2 },
+                tiles: Some(4),
+                colour: true,
+            })
+        );
+        assert!(parse("path --down 0").is_err());
+        assert!(parse("path --right -1").is_err());
+        assert!(parse("path --day 3").is_err());
+    }
+
+    #[test]
+    fn passports_are_strict_unless_part_1() {
+        assert_eq!(
+            parse("passports --schema schemas/passport.toml").unwrap(),
+            Command::Passports(PassportsOptions {
+                input: None,
+                schema: Some(String::from("schemas/passport.toml")),
+                strict: true,
+                export: None,
+            })
+        );
+        assert_eq!(
+            parse("passports --part 1 --input foo").unwrap(),
+            Command::Passports(PassportsOptions {
+                input: Some(String::from("foo")),
+                schema: None,
+                strict: false,
+                export: None,
+            })
+        );
+        assert_eq!(
+            parse("passports --export jsonl").unwrap(),
+            Command::Passports(PassportsOptions {
+                input: None,
+                schema: None,
+                strict: true,
+                export: Some(Export::JsonLines),
+            })
+        );
+        assert!(parse("passports --export json").is_err());
+        assert!(parse("passports --export csv --part 1").is_err());
+        assert!(parse("passports --export csv --schema foo").is_err());
+        assert!(parse("passports --day 4").is_err());
+        assert!(parse("run --schema foo").is_err());
+    }
+
+    #[test]
+    fn bags_writes_to_bags_dot_by_default() {
+        assert_eq!(
+            parse("bags").unwrap(),
+            Command::Bags(BagsOptions {
+                input: None,
+                highlight: None,
+                output: String::from("bags.dot"),
+            })
+        );
+        assert_eq!(
+            parse_args(
+                ["bags", "--highlight", "shiny gold", "--output", "out.dot"]
+                    .iter()
+                    .map(|s| s.to_string())
+            )
+            .unwrap(),
+            Command::Bags(BagsOptions {
+                input: None,
+                highlight: Some(String::from("shiny gold")),
+                output: String::from("out.dot"),
+            })
+        );
+        assert!(parse("bags --day 7").is_err());
+        assert!(parse("bags --output").is_err());
+        assert!(parse("run --highlight red").is_err());
+    }
+
+    #[test]
+    fn invalid_args() {
+        assert!(parse("run --day 0").is_err());
+        assert!(parse("run --day 26").is_err());
+        assert!(parse("run --days 8-3").is_err());
+        assert!(parse("run --part 3").is_err());
+        assert!(parse("run --all --day 3").is_err());
+        assert!(parse("run --day").is_err());
+        assert!(parse("fly").is_err());
+        assert!(parse("run --format json").is_err());
+        assert!(parse("time --format xml").is_err());
+        assert!(parse("run --answers answers.toml").is_err());
+    }
+}
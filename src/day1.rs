@@ -1,25 +1,257 @@
-pub fn part1(expenses: &[u32]) -> u32 {
-    for (pos1, expense1) in expenses.iter().enumerate() {
-        for (pos2, expense2) in expenses.iter().enumerate() {
-            if pos1 != pos2 && expense1 + expense2 == 2020 {
-                return expense1 * expense2;
-            }
-        }
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<u32>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).map_or_else(
+            || Answer::from("no two expenses add up to 2020"),
+            Answer::from,
+        )
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).map_or_else(
+            || Answer::from("no three expenses add up to 2020"),
+            Answer::from,
+        )
+    }
+}
+
+/// the sum the elves are looking for in their expense report
+pub const TARGET_SUM: u32 = 2020;
+
+/// Finds the two expenses that add up to 2020 and multiplies them together
+pub fn part1(expenses: &[u32]) -> Option<u64> {
+    find_k_sum(expenses, 2, TARGET_SUM).map(|combination| combination.product())
+}
+
+/// Finds the three expenses that add up to 2020 and multiplies them together
+pub fn part2(expenses: &[u32]) -> Option<u64> {
+    find_k_sum(expenses, 3, TARGET_SUM).map(|combination| combination.product())
+}
+
+/// A combination of entries from a list of values, with the values in ascending order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KSum {
+    /// where each value is in the original list
+    pub indices: Vec<usize>,
+    pub values: Vec<u32>,
+}
+
+impl KSum {
+    pub fn product(&self) -> u64 {
+        self.values.iter().map(|v| *v as u64).product()
     }
+}
+
+/// Finds `k` different entries of `values` that add up to `target`, if there are any
+pub fn find_k_sum(values: &[u32], k: usize, target: u32) -> Option<KSum> {
+    let mut result = None;
+
+    search_k_sum(
+        &sorted_with_indices(values),
+        k,
+        target as u64,
+        &mut Vec::new(),
+        &mut |found| {
+            result = Some(to_k_sum(found));
+            true
+        },
+    );
+
+    result
+}
+
+/// Finds every combination of `k` different entries of `values` that add up to `target`.
+/// Repeated values in the list only produce the combination once.
+pub fn find_all_k_sums(values: &[u32], k: usize, target: u32) -> Vec<KSum> {
+    let mut results = Vec::new();
+
+    search_k_sum(
+        &sorted_with_indices(values),
+        k,
+        target as u64,
+        &mut Vec::new(),
+        &mut |found| {
+            results.push(to_k_sum(found));
+            false
+        },
+    );
+
+    results
+}
+
+/// a value and where it is in the original list
+type Entry = (u32, usize);
 
-    panic!("couldnt find two inputs that add to 2020");
+/// every value with its index, sorted by value
+fn sorted_with_indices(values: &[u32]) -> Vec<Entry> {
+    let mut sorted: Vec<Entry> = values.iter().copied().zip(0..).collect();
+    sorted.sort_unstable();
+    sorted
 }
 
-pub fn part2(expenses: &[u32]) -> u32 {
-    for (pos1, expense1) in expenses.iter().enumerate() {
-        for (pos2, expense2) in expenses.iter().enumerate() {
-            for (pos3, expense3) in expenses.iter().enumerate() {
-                if pos1 != pos2 && pos2 != pos3 && expense1 + expense2 + expense3 == 2020 {
-                    return expense1 * expense2 * expense3;
+fn to_k_sum(found: &[Entry]) -> KSum {
+    let mut found = found.to_vec();
+    found.sort_unstable();
+
+    KSum {
+        indices: found.iter().map(|(_, index)| *index).collect(),
+        values: found.iter().map(|(value, _)| *value).collect(),
+    }
+}
+
+/// Walks every distinct combination of `k` entries of `sorted` adding up to `target`, calling
+/// `found` with each one (plus the entries already `chosen`). Stops as soon as `found` returns
+/// true, and returns whether it stopped.
+///
+/// The last two entries are found with two pointers closing in from both ends of the sorted
+/// list, so the whole search is `O(n^(k-1))` rather than `O(n^k)`.
+fn search_k_sum(
+    sorted: &[Entry],
+    k: usize,
+    target: u64,
+    chosen: &mut Vec<Entry>,
+    found: &mut dyn FnMut(&[Entry]) -> bool,
+) -> bool {
+    match k {
+        0 => target == 0 && found(chosen),
+        1 => match sorted.binary_search_by(|(value, _)| (*value as u64).cmp(&target)) {
+            Ok(i) => {
+                chosen.push(sorted[i]);
+                let stop = found(chosen);
+                chosen.pop();
+                stop
+            }
+            Err(_) => false,
+        },
+        2 => {
+            if sorted.len() < 2 {
+                return false;
+            }
+
+            let (mut low, mut high) = (0, sorted.len() - 1);
+            while low < high {
+                let sum = sorted[low].0 as u64 + sorted[high].0 as u64;
+
+                if sum < target {
+                    low += 1;
+                } else if sum > target {
+                    high -= 1;
+                } else {
+                    chosen.push(sorted[low]);
+                    chosen.push(sorted[high]);
+                    let stop = found(chosen);
+                    chosen.truncate(chosen.len() - 2);
+                    if stop {
+                        return true;
+                    }
+
+                    // skip past repeats so each combination of values is only found once
+                    let (low_value, high_value) = (sorted[low].0, sorted[high].0);
+                    while low < high && sorted[low].0 == low_value {
+                        low += 1;
+                    }
+                    while low < high && sorted[high].0 == high_value {
+                        high -= 1;
+                    }
+                }
+            }
+
+            false
+        }
+        _ => {
+            for i in 0..sorted.len() {
+                if i > 0 && sorted[i].0 == sorted[i - 1].0 {
+                    continue;
+                }
+
+                let value = sorted[i].0 as u64;
+                // everything after this is at least as big, so there is nothing left to find
+                if value * (k as u64) > target {
+                    break;
+                }
+
+                chosen.push(sorted[i]);
+                let stop = search_k_sum(&sorted[(i + 1)..], k - 1, target - value, chosen, found);
+                chosen.pop();
+                if stop {
+                    return true;
                 }
             }
+
+            false
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_all_k_sums, find_k_sum, part1, part2, KSum};
+
+    const EXPENSES: [u32; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    #[test]
+    fn part1_test() {
+        assert_eq!(part1(&EXPENSES), Some(514579));
+    }
+
+    #[test]
+    fn part2_test() {
+        assert_eq!(part2(&EXPENSES), Some(241861950));
+    }
 
-    panic!("couldnt find three inputs that add to 2020");
+    #[test]
+    fn find_k_sum_returns_indices() {
+        assert_eq!(
+            find_k_sum(&EXPENSES, 3, 2020),
+            Some(KSum {
+                indices: vec![2, 4, 1],
+                values: vec![366, 675, 979],
+            })
+        );
+    }
+
+    #[test]
+    fn find_k_sum_uses_each_entry_once() {
+        assert_eq!(find_k_sum(&[1010, 5], 2, 2020), None);
+        assert_eq!(
+            find_k_sum(&[1010, 5, 1010], 2, 2020).map(|k_sum| k_sum.indices),
+            Some(vec![0, 2])
+        );
+        assert_eq!(find_k_sum(&EXPENSES, 7, 2020), None);
+    }
+
+    #[test]
+    fn find_all_k_sums_skips_repeated_values() {
+        let all = find_all_k_sums(&[1, 2, 3, 4, 5, 3], 2, 6);
+        let values: Vec<Vec<u32>> = all.into_iter().map(|k_sum| k_sum.values).collect();
+
+        assert_eq!(values, vec![vec![1, 5], vec![2, 4], vec![3, 3]]);
+    }
+
+    #[test]
+    fn find_k_sum_edge_cases() {
+        assert!(find_k_sum(&[], 2, 2020).is_none());
+        assert_eq!(
+            find_k_sum(&[4, 9], 1, 9).map(|k_sum| k_sum.indices),
+            Some(vec![1])
+        );
+        assert_eq!(
+            find_k_sum(&[4, 9], 0, 0).map(|k_sum| k_sum.indices),
+            Some(vec![])
+        );
+    }
 }
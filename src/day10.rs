@@ -1,5 +1,30 @@
 use std::collections::HashMap;
 
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Vec<usize>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 pub fn part1(input: &[usize]) -> usize {
     let mut working_set = input.to_vec().clone();
 
@@ -16,10 +41,10 @@ pub fn part1(input: &[usize]) -> usize {
     working_set.iter().for_each(|value| {
         // println!("prev: {:?}, value: {:?}", prev_value, value);
         match value {
-            v if v.clone() == prev_value + 3 => {
+            v if *v == prev_value + 3 => {
                 num_three_jolt_diff += 1;
             }
-            v if v.clone() == prev_value + 1 => {
+            v if *v == prev_value + 1 => {
                 num_one_jolt_diff += 1;
             }
             _ => panic!("should not have happened :|"),
@@ -28,7 +53,7 @@ pub fn part1(input: &[usize]) -> usize {
         // println!("1-jolt-diff: {:?}", num_one_jolt_diff);
         // println!("3-jolt-diff: {:?}", num_three_jolt_diff);
 
-        prev_value = value.clone();
+        prev_value = *value;
     });
 
     // add one more 3-jolt diff because:
@@ -42,7 +67,7 @@ pub fn part1(input: &[usize]) -> usize {
 // we need to find all combinations where we can reach 22
 // this works for the tests, but will take forever to do for the larger dataset
 pub fn part2(input: &[usize]) -> usize {
-    let mut working_set = input.clone().to_owned();
+    let mut working_set = input.to_owned();
     working_set.sort();
 
     // max joltage supported is the max in the list + 3
@@ -81,28 +106,22 @@ pub fn part2(input: &[usize]) -> usize {
             .filter(|(_, v)| ((**v) > curr_list_last) && (**v - curr_list_last) <= 3)
             .map(|(i, v)| {
                 // now we remove that number from the list
-                let index = i.clone();
+                let index = i;
 
                 // println!("last in curr list: {:?} -> {:?}", curr_list_last, v);
 
                 curr_list.to_vec().push(*v);
 
-                find_paths(
-                    max_joltage,
-                    *v,
-                    curr_list.as_mut(),
-                    &remaining_list[index..],
-                    cache,
-                )
+                find_paths(max_joltage, *v, curr_list, &remaining_list[index..], cache)
             })
             .sum();
 
         cache.insert(curr_list_last, sum_of_results);
 
         sum_of_results
-    };
+    }
 
-    find_paths(max_joltage, 0, &mut vec![0], &mut working_set, &mut cache)
+    find_paths(max_joltage, 0, &mut [0], &working_set, &mut cache)
 }
 
 #[cfg(test)]
@@ -142,12 +161,12 @@ mod tests {
 34
 10
 3";
-        let mut lines: Vec<usize> = input
+        let lines: Vec<usize> = input
             .lines()
             .map(|line| line.parse::<usize>().unwrap())
             .collect();
 
-        let result = part1(&mut lines);
+        let result = part1(&lines);
 
         assert_eq!(22 * 10, result);
     }
@@ -185,12 +204,12 @@ mod tests {
 34
 10
 3";
-        let mut lines: Vec<usize> = input
+        let lines: Vec<usize> = input
             .lines()
             .map(|line| line.parse::<usize>().unwrap())
             .collect();
 
-        let result = part2(&mut lines);
+        let result = part2(&lines);
 
         assert_eq!(19208, result);
     }
@@ -1,27 +1,45 @@
-use std::{fmt::Debug, str::FromStr};
+use std::{convert::TryFrom, fmt};
 
-pub fn part1(input: &[String]) -> usize {
-    let mut seats = GameOfSeatingLife::new(input);
+use crate::{
+    grid::{Grid, DIRECTIONS_8},
+    input_utils::LineError,
+    solution::{Answer, Solution},
+};
 
-    let mut count = 1;
-    loop {
-        // println!("tick #{}", count);
-        if seats.tick() == 0 {
-            break;
-        }
+pub struct Day11;
 
-        // println!("map:\n{:?}", map);
-        count += 1;
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input = Grid<FerryPosition>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        Grid::parse(input)
     }
 
-    seats.get_num_occupied_seats()
+    fn part1(input: &Self::Input) -> Answer {
+        settle(input.clone(), GameOfSeatingLife::tick).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        settle(input.clone(), GameOfSeatingLife::tick2).into()
+    }
+}
+
+pub fn part1(input: &[String]) -> usize {
+    settle(Grid::parse(input).unwrap(), GameOfSeatingLife::tick)
 }
 
 pub fn part2(input: &[String]) -> usize {
-    let mut seats = GameOfSeatingLife::new(input);
+    settle(Grid::parse(input).unwrap(), GameOfSeatingLife::tick2)
+}
+
+/// runs the rules until no seat changes, returning how many seats end up occupied
+fn settle(start: Grid<FerryPosition>, tick: fn(&mut GameOfSeatingLife) -> usize) -> usize {
+    let mut seats = GameOfSeatingLife::new(start);
 
     loop {
-        if seats.tick2() == 0 {
+        if tick(&mut seats) == 0 {
             break;
         }
     }
@@ -30,36 +48,18 @@ pub fn part2(input: &[String]) -> usize {
 }
 
 struct GameOfSeatingLife {
-    position: Vec<Vec<FerryPosition>>,
+    position: Grid<FerryPosition>,
 }
 
-impl Debug for GameOfSeatingLife {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut result = String::new();
-        self.position.iter().for_each(|line| {
-            line.iter().for_each(|p| {
-                result = format!("{}{:?} ", result, p);
-            });
-            result = format!("{}\n", result);
-        });
-        f.write_str(&result)
+impl fmt::Debug for GameOfSeatingLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.position)
     }
 }
 
 impl GameOfSeatingLife {
-    pub fn new(input: &[String]) -> Self {
-        let start_map = input
-            .iter()
-            .map(|line| {
-                line.chars()
-                    .map(|c| FerryPosition::from_str(&c.to_string()).unwrap())
-                    .collect()
-            })
-            .collect();
-
-        Self {
-            position: start_map,
-        }
+    pub fn new(position: Grid<FerryPosition>) -> Self {
+        Self { position }
     }
 
     // for every tick, we must apply the following rules:
@@ -67,251 +67,75 @@ impl GameOfSeatingLife {
     // If a seat is occupied (#) and four or more seats adjacent to it are also occupied, the seat becomes empty.
     // Otherwise, the seat's state does not change.
     pub fn tick(&mut self) -> usize {
-        let mut new_ferry_map = self.position.clone();
-        let mut num_state_changes = 0;
+        self.step(4, |grid, row, column| {
+            grid.neighbours8(row, column)
+                .filter(|(_, p)| **p == FerryPosition::OccupiedSeat)
+                .count()
+        })
+    }
 
-        self.position
-            .iter()
-            .enumerate()
-            .for_each(|(row_index, line)| {
-                line.iter().enumerate().for_each(|(column_index, p)| {
-                    // the main logic
-                    match p {
-                        FerryPosition::EmptySeat => {
-                            // seat is empty
-                            let adjacent_occupied_seats: Vec<FerryPosition> = self
-                                .get_adjacent_states(row_index as isize, column_index as isize)
-                                .iter()
-                                .filter_map(|p| {
-                                    if *p != FerryPosition::OccupiedSeat {
-                                        return None;
-                                    }
-
-                                    Some(p.to_owned())
-                                })
-                                .collect();
-
-                            // println!(
-                            //     "adjacent occupied seats @ {},{}: {:?}",
-                            //     row_index, column_index, adjacent_occupied_seats
-                            // );
-                            if adjacent_occupied_seats.len() > 0 {
-                                return;
-                            }
-
-                            // all unoccupied! Set is occupied
-                            // println!(
-                            //     "swapping @ {},{} to {:?}",
-                            //     row_index,
-                            //     column_index,
-                            //     FerryPosition::OccupiedSeat
-                            // );
-                            num_state_changes += 1;
-                            new_ferry_map[row_index][column_index] = FerryPosition::OccupiedSeat
-                        }
-                        FerryPosition::OccupiedSeat => {
-                            let adjacent_occupied_seats: Vec<FerryPosition> = self
-                                .get_adjacent_states(row_index as isize, column_index as isize)
-                                .iter()
-                                .filter_map(|p| {
-                                    if *p != FerryPosition::OccupiedSeat {
-                                        return None;
-                                    }
-
-                                    Some(p.to_owned())
-                                })
-                                .collect();
-                            // println!(
-                            //     "adjacent occupied seats @ {},{}: {:?}",
-                            //     row_index, column_index, adjacent_occupied_seats
-                            // );
-                            if adjacent_occupied_seats.len() < 4 {
-                                return;
-                            }
-
-                            // four are occupied! Seat is now empty
-                            num_state_changes += 1;
-                            new_ferry_map[row_index][column_index] = FerryPosition::EmptySeat
-                        }
-                        FerryPosition::Floor => {}
-                    };
+    // part 2 looks at the first seat visible in each direction instead of the adjacent
+    // positions, and it takes five occupied seats for people to leave
+    pub fn tick2(&mut self) -> usize {
+        self.step(5, |grid, row, column| {
+            DIRECTIONS_8
+                .iter()
+                .filter_map(|direction| {
+                    grid.ray(row, column, *direction)
+                        .map(|(_, p)| *p)
+                        .find(|p| *p != FerryPosition::Floor)
                 })
-            });
-
-        // println!("new map: {:?}", new_ferry_map);
-
-        self.position = new_ferry_map;
-        num_state_changes
+                .filter(|p| *p == FerryPosition::OccupiedSeat)
+                .count()
+        })
     }
 
-    pub fn tick2(&mut self) -> usize {
+    /// applies one round of the rules, returning how many seats changed
+    fn step<F>(&mut self, crowded: usize, occupied_around: F) -> usize
+    where
+        F: Fn(&Grid<FerryPosition>, usize, usize) -> usize,
+    {
         let mut new_ferry_map = self.position.clone();
         let mut num_state_changes = 0;
 
-        self.position
-            .iter()
-            .enumerate()
-            .for_each(|(row_index, line)| {
-                line.iter().enumerate().for_each(|(column_index, p)| {
-                    // the main logic
-                    match p {
-                        FerryPosition::EmptySeat => {
-                            // seat is empty
-                            let adjacent_occupied_seats: Vec<FerryPosition> = self
-                                .find_closest_seat_in_each_direction_states(
-                                    row_index as isize,
-                                    column_index as isize,
-                                )
-                                .iter()
-                                .filter_map(|p| {
-                                    if *p != FerryPosition::OccupiedSeat {
-                                        return None;
-                                    }
-
-                                    Some(p.to_owned())
-                                })
-                                .collect();
-
-                            if adjacent_occupied_seats.len() > 0 {
-                                return;
-                            }
-
-                            num_state_changes += 1;
-                            new_ferry_map[row_index][column_index] = FerryPosition::OccupiedSeat
-                        }
-                        FerryPosition::OccupiedSeat => {
-                            let adjacent_occupied_seats: Vec<FerryPosition> = self
-                                .find_closest_seat_in_each_direction_states(
-                                    row_index as isize,
-                                    column_index as isize,
-                                )
-                                .iter()
-                                .filter_map(|p| {
-                                    if *p != FerryPosition::OccupiedSeat {
-                                        return None;
-                                    }
-
-                                    Some(p.to_owned())
-                                })
-                                .collect();
-                            if adjacent_occupied_seats.len() < 5 {
-                                return;
-                            }
-
-                            // five are occupied! Seat is now empty
-                            num_state_changes += 1;
-                            new_ferry_map[row_index][column_index] = FerryPosition::EmptySeat
-                        }
-                        FerryPosition::Floor => {}
-                    };
-                })
-            });
-
-        self.position = new_ferry_map;
-        num_state_changes
-    }
-
-    fn find_closest_seat_in_each_direction_states(
-        &self,
-        row_index: isize,
-        column_index: isize,
-    ) -> Vec<FerryPosition> {
-        [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ]
-        .iter()
-        .filter_map(|(row_delta, column_delta)| {
-            let mut check_row = row_index;
-            let mut check_column = column_index;
-
-            loop {
-                check_row += row_delta;
-                check_column += column_delta;
-
-                // check to see if it is in bounds!
-                if !self.is_in_bounds(check_row, check_column) {
-                    return None;
+        for ((row, column), p) in self.position.iter() {
+            let next = match p {
+                FerryPosition::EmptySeat if occupied_around(&self.position, row, column) == 0 => {
+                    FerryPosition::OccupiedSeat
                 }
-
-                match self.get_state_at_position(check_row as usize, check_column as usize) {
-                    FerryPosition::Floor => {
-                        // we found a floor! continue
-                    }
-                    v => return Some(v),
+                FerryPosition::OccupiedSeat
+                    if occupied_around(&self.position, row, column) >= crowded =>
+                {
+                    FerryPosition::EmptySeat
                 }
-            }
-        })
-        .collect()
-    }
+                _ => continue,
+            };
 
-    fn get_adjacent_states(&self, row_index: isize, column_index: isize) -> Vec<FerryPosition> {
-        [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ]
-        .iter()
-        .filter_map(|(row_delta, column_delta)| {
-            if !self.is_in_bounds(
-                (row_index as isize) + row_delta,
-                (column_index as isize) + column_delta,
-            ) {
-                return None;
-            }
-            Some(self.get_state_at_position(
-                (row_index + row_delta) as usize,
-                (column_index + column_delta) as usize,
-            ))
-        })
-        .collect()
+            num_state_changes += 1;
+            *new_ferry_map.get_mut(row, column).unwrap() = next;
+        }
+
+        self.position = new_ferry_map;
+        num_state_changes
     }
 
     fn get_num_occupied_seats(&self) -> usize {
         self.position
             .iter()
-            .flatten()
-            .filter(|p| **p == FerryPosition::OccupiedSeat)
+            .filter(|(_, p)| **p == FerryPosition::OccupiedSeat)
             .count()
     }
-
-    fn is_in_bounds(&self, row_index: isize, column_index: isize) -> bool {
-        row_index >= 0
-            && (row_index as usize) < self.position.len()
-            && column_index >= 0
-            && (column_index as usize) < self.position[0].len()
-    }
-
-    fn get_state_at_position(&self, row_index: usize, column_index: usize) -> FerryPosition {
-        self.position
-            .get(row_index)
-            .unwrap()
-            .get(column_index)
-            .unwrap()
-            .to_owned()
-    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum FerryPosition {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FerryPosition {
     EmptySeat,
     OccupiedSeat,
     Floor,
 }
 
-impl Debug for FerryPosition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FerryPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
             FerryPosition::OccupiedSeat => "#",
             FerryPosition::EmptySeat => "L",
@@ -320,18 +144,15 @@ impl Debug for FerryPosition {
     }
 }
 
-impl FromStr for FerryPosition {
-    type Err = std::io::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "." => Ok(Self::Floor),
-            "L" => Ok(Self::EmptySeat),
-            "#" => Ok(Self::OccupiedSeat),
-            v => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("invalid input: {:?}. Expected one of [L, ., #]", v),
-            )),
+impl TryFrom<char> for FerryPosition {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Self::Floor),
+            'L' => Ok(Self::EmptySeat),
+            '#' => Ok(Self::OccupiedSeat),
+            v => Err(format!("invalid input: {:?}. Expected one of [L, ., #]", v)),
         }
     }
 }
@@ -339,6 +160,7 @@ impl FromStr for FerryPosition {
 #[cfg(test)]
 mod tests {
     use super::GameOfSeatingLife;
+    use crate::grid::Grid;
 
     #[test]
     fn game_of_seating_life_test() {
@@ -355,7 +177,7 @@ L.LLLLL.LL";
 
         let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
 
-        let mut map = GameOfSeatingLife::new(&lines);
+        let mut map = GameOfSeatingLife::new(Grid::parse(&lines).unwrap());
 
         let mut count = 1;
         loop {
@@ -386,7 +208,7 @@ L.LLLLL.LL";
 
         let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
 
-        let mut map = GameOfSeatingLife::new(&lines);
+        let mut map = GameOfSeatingLife::new(Grid::parse(&lines).unwrap());
 
         let mut count = 1;
         loop {
@@ -1,11 +1,39 @@
 use std::{fmt::Debug, str::FromStr};
 
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Input = Vec<Action>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        sail(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        sail_by_waypoint(input).into()
+    }
+}
+
 pub fn part1(input: &[String]) -> usize {
-    let actions: Vec<Action> = input
-        .iter()
-        .map(|line| Action::from_str(line).unwrap())
-        .collect();
+    sail(&input_utils::parse_all_as(input).unwrap())
+}
 
+pub fn part2(input: &[String]) -> usize {
+    sail_by_waypoint(&input_utils::parse_all_as(input).unwrap())
+}
+
+fn sail(actions: &[Action]) -> usize {
     let mut ship = Ship::new(Direction::East);
 
     actions.iter().for_each(|action| {
@@ -15,12 +43,7 @@ pub fn part1(input: &[String]) -> usize {
     ship.manhattan_distance()
 }
 
-pub fn part2(input: &[String]) -> usize {
-    let actions: Vec<Action> = input
-        .iter()
-        .map(|line| Action::from_str(line).unwrap())
-        .collect();
-
+fn sail_by_waypoint(actions: &[Action]) -> usize {
     let mut ship = Ship::new_with_waypoint_location(Direction::East, 10, 1);
 
     actions.iter().for_each(|action| {
@@ -45,7 +68,7 @@ enum Movement {
 }
 
 #[derive(Debug)]
-enum Action {
+pub enum Action {
     North(usize),
     East(usize),
     South(usize),
@@ -59,24 +82,37 @@ impl FromStr for Action {
     type Err = std::io::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let direction = s.get(..1).unwrap();
-        let distance = s
-            .get(1..)
-            .unwrap()
+        let invalid_input =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+
+        let mut chars = s.chars();
+        let direction = chars.next().ok_or_else(|| {
+            invalid_input("empty action, expected something like \"F10\"".to_string())
+        })?;
+        let distance = chars.as_str();
+        let distance = distance
             .parse::<usize>()
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            .map_err(|e| invalid_input(format!("{:?} is not a valid number: {}", distance, e)))?;
+
+        // the ship and waypoint can only turn in quarter turns
+        if matches!(direction, 'L' | 'R') && distance % 90 != 0 {
+            return Err(invalid_input(format!(
+                "turn of {} degrees is not a multiple of 90",
+                distance
+            )));
+        }
 
         match direction {
-            "N" => Ok(Self::North(distance)),
-            "E" => Ok(Self::East(distance)),
-            "S" => Ok(Self::South(distance)),
-            "W" => Ok(Self::West(distance)),
-            "L" => Ok(Self::Left(distance)),
-            "R" => Ok(Self::Right(distance)),
-            "F" => Ok(Self::Forward(distance)),
+            'N' => Ok(Self::North(distance)),
+            'E' => Ok(Self::East(distance)),
+            'S' => Ok(Self::South(distance)),
+            'W' => Ok(Self::West(distance)),
+            'L' => Ok(Self::Left(distance)),
+            'R' => Ok(Self::Right(distance)),
+            'F' => Ok(Self::Forward(distance)),
             v => Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                format!("invalid direction {}", v),
+                format!("invalid direction {:?}", v),
             )),
         }
     }
@@ -188,14 +224,15 @@ impl Ship {
 
     fn rotate_wavepoint(&mut self, movement: Movement, angle: usize) {
         let angle = match movement {
-            Movement::Right => angle,
-            Movement::Left => 360 - angle,
+            Movement::Right => angle % 360,
+            Movement::Left => (360 - angle % 360) % 360,
         };
 
         let (new_waypoint_x, new_waypoint_y) = match angle {
-            x if x == 90 => (self.waypoint_y, -self.waypoint_x),
-            x if x == 180 => (-self.waypoint_x, -self.waypoint_y),
-            x if x == 270 => (-self.waypoint_y, self.waypoint_x),
+            0 => (self.waypoint_x, self.waypoint_y),
+            90 => (self.waypoint_y, -self.waypoint_x),
+            180 => (-self.waypoint_x, -self.waypoint_y),
+            270 => (-self.waypoint_y, self.waypoint_x),
             x => panic!("invalid angle {}", x),
         };
 
@@ -228,7 +265,7 @@ impl Ship {
             Movement::Left => {
                 let mut i: isize = (direction_index as isize) - num_direction_changes;
                 if i < 0 {
-                    i = direction_ordering.len() as isize + i;
+                    i += direction_ordering.len() as isize;
                 }
 
                 i as usize
@@ -250,7 +287,8 @@ impl Ship {
 mod tests {
     use std::str::FromStr;
 
-    use super::{Action, Ship};
+    use super::{Action, Day12, Ship};
+    use crate::solution::Solution;
 
     #[test]
     fn test_ship_movement() {
@@ -302,4 +340,27 @@ F11";
 
         assert_eq!(ship.manhattan_distance(), 286);
     }
+
+    #[test]
+    fn bad_actions_are_errors() {
+        assert!(Action::from_str("").is_err());
+        assert!(Action::from_str("X3").is_err());
+        assert!(Action::from_str("F").is_err());
+        assert!(Action::from_str("F-3").is_err());
+        assert!(Action::from_str("L45").is_err());
+
+        let lines = vec!["F10".to_string(), "".to_string()];
+        let error = <Day12 as Solution>::parse(&lines).unwrap_err();
+        assert_eq!(error.line_number, 2);
+    }
+
+    #[test]
+    fn full_turns_leave_the_waypoint_alone() {
+        let actions: Vec<Action> = ["L360", "R0", "F1"]
+            .iter()
+            .map(|line| Action::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(super::sail_by_waypoint(&actions), 11);
+    }
 }
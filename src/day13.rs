@@ -1,93 +1,288 @@
-use std::u32::MAX;
+use crate::{
+    input_utils::LineError,
+    solution::{Answer, Solution},
+};
 
-pub fn part1(input: &[String]) -> u32 {
-    let mut lines = input.iter();
-    let earliest_timestamp = lines.next().unwrap().parse::<u32>().unwrap();
-    let bus_ids = get_all_bus_ids(lines.next().unwrap());
+pub struct Day13;
 
-    println!("all bus times: {:?}", bus_ids);
+impl Solution for Day13 {
+    const DAY: u8 = 13;
 
-    let mut closest_bus_id = 0;
-    let mut closest_diff = MAX;
-    for bus_id in bus_ids.iter() {
-        let bus_id_u32 = bus_id.parse::<u32>().unwrap();
-        let curr_bus_earliest = get_bus_times_for_id(bus_id_u32, earliest_timestamp)
-            .iter()
-            .max()
-            .unwrap()
-            .clone();
+    type Input = BusSchedule;
 
-        let curr_diff = curr_bus_earliest - earliest_timestamp;
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        BusSchedule::parse(input)
+    }
 
-        if curr_diff < closest_diff {
-            closest_diff = curr_diff;
-            closest_bus_id = bus_id_u32;
-        }
+    fn part1(input: &Self::Input) -> Answer {
+        earliest_bus_score(input).into()
     }
 
-    closest_diff * closest_bus_id
+    fn part2(input: &Self::Input) -> Answer {
+        input
+            .earliest_aligned_timestamp()
+            .map_or_else(|| Answer::from("the buses never line up"), Answer::from)
+    }
 }
 
-fn get_all_bus_ids<T>(line: T) -> Vec<String>
-where
-    T: Into<String>,
-{
-    line.into()
-        .split_terminator(",")
-        .filter(|s| *s != "x")
-        .map(|s| s.to_string())
-        .collect()
+/// Finds the first bus to leave after we arrive, and multiplies its id by how long we wait
+pub fn part1(input: &[String]) -> u64 {
+    earliest_bus_score(&BusSchedule::parse(input).unwrap())
+}
+
+/// Finds the earliest timestamp where the first bus departs, and every other bus departs the
+/// same number of minutes later as its position in the list. The first line of the notes is
+/// not needed. `None` if the buses never line up.
+pub fn part2(input: &[String]) -> Option<u64> {
+    BusSchedule::parse(input)
+        .unwrap()
+        .earliest_aligned_timestamp()
+}
+
+fn earliest_bus_score(schedule: &BusSchedule) -> u64 {
+    let (bus_id, wait_time) = schedule
+        .wait_times()
+        .into_iter()
+        .min_by_key(|(_, wait_time)| *wait_time)
+        .unwrap();
+
+    bus_id * wait_time
 }
 
-fn get_bus_times_for_id(id: u32, earliest_timestamp: u32) -> Vec<u32> {
-    let mut bus_times = Vec::new();
-    let mut current_time = id;
-    loop {
-        bus_times.push(current_time);
+/// A bus in service. It departs every `id` minutes, and is listed `offset` entries into the
+/// schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bus {
+    pub offset: u64,
+    pub id: u64,
+}
+
+impl Bus {
+    /// the first departure at or after `timestamp`
+    pub fn next_departure(&self, timestamp: u64) -> u64 {
+        timestamp.div_ceil(self.id) * self.id
+    }
+
+    pub fn wait_time(&self, timestamp: u64) -> u64 {
+        self.next_departure(timestamp) - timestamp
+    }
+}
+
+/// The notes from the shuttle company: when we can first leave, and the buses in service in
+/// the order they are listed. Out of service (`x`) entries are dropped, but every bus keeps its
+/// offset in the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusSchedule {
+    pub earliest_timestamp: u64,
+    buses: Vec<Bus>,
+}
 
-        if current_time > earliest_timestamp {
-            break;
+impl BusSchedule {
+    pub fn parse(input: &[String]) -> Result<Self, LineError> {
+        let line = |i: usize| {
+            input
+                .get(i)
+                .ok_or_else(|| LineError::new(i, "", "the notes should have two lines"))
+        };
+
+        let timestamp_line = line(0)?;
+        let earliest_timestamp = timestamp_line
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| LineError::new(0, timestamp_line, e))?;
+
+        let bus_line = line(1)?;
+        let mut buses = Vec::new();
+        for (offset, entry) in bus_line.trim().split_terminator(',').enumerate() {
+            if entry == "x" {
+                continue;
+            }
+
+            let id = entry.parse::<u64>().map_err(|e| {
+                LineError::new(
+                    1,
+                    bus_line,
+                    format!("bus {:?} is not a number: {}", entry, e),
+                )
+            })?;
+            if id == 0 {
+                return Err(LineError::new(
+                    1,
+                    bus_line,
+                    "bus ids must be greater than 0",
+                ));
+            }
+
+            buses.push(Bus {
+                offset: offset as u64,
+                id,
+            });
+        }
+
+        if buses.is_empty() {
+            return Err(LineError::new(1, bus_line, "there are no buses in service"));
         }
 
-        current_time += id;
+        Ok(Self {
+            earliest_timestamp,
+            buses,
+        })
     }
 
-    bus_times
+    pub fn buses(&self) -> &[Bus] {
+        &self.buses
+    }
+
+    pub fn bus(&self, bus_id: u64) -> Option<&Bus> {
+        self.buses.iter().find(|bus| bus.id == bus_id)
+    }
+
+    /// the first departure of the bus at or after `timestamp`, if the bus is in service
+    pub fn next_departure(&self, bus_id: u64, timestamp: u64) -> Option<u64> {
+        self.bus(bus_id).map(|bus| bus.next_departure(timestamp))
+    }
+
+    /// `(bus_id, wait_time)` for every bus, waiting from the earliest timestamp we can leave
+    pub fn wait_times(&self) -> Vec<(u64, u64)> {
+        self.buses
+            .iter()
+            .map(|bus| (bus.id, bus.wait_time(self.earliest_timestamp)))
+            .collect()
+    }
+
+    /// The earliest timestamp where every bus departs `offset` minutes after it, if there is
+    /// one
+    pub fn earliest_aligned_timestamp(&self) -> Option<u64> {
+        earliest_aligned_timestamp(&self.buses)
+    }
+}
+
+/// Solves `t + offset ≡ 0 (mod bus_id)` for every bus using the Chinese Remainder Theorem,
+/// folding one bus at a time into a single `t ≡ remainder (mod modulus)` constraint. Bus ids
+/// do not have to be coprime; `None` is returned if the constraints contradict each other.
+fn earliest_aligned_timestamp(buses: &[Bus]) -> Option<u64> {
+    let mut remainder: i128 = 0;
+    let mut modulus: i128 = 1;
+
+    for bus in buses.iter() {
+        let bus_id = bus.id as i128;
+        let target = (-(bus.offset as i128)).rem_euclid(bus_id);
+
+        // find k where remainder + modulus * k ≡ target (mod bus_id)
+        let (gcd, inverse, _) = extended_gcd(modulus, bus_id);
+        if (target - remainder) % gcd != 0 {
+            return None;
+        }
+
+        let step = bus_id / gcd;
+        let k = ((target - remainder) / gcd * inverse).rem_euclid(step);
+
+        remainder += modulus * k;
+        modulus *= step;
+        remainder = remainder.rem_euclid(modulus);
+    }
+
+    Some(remainder as u64)
+}
+
+/// returns `(gcd, x, y)` where `a * x + b * y = gcd`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+
+    let (gcd, x, y) = extended_gcd(b, a % b);
+    (gcd, y, x - (a / b) * y)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::u32::MAX;
+    use super::{part1, Bus, BusSchedule, Day13};
+    use crate::solution::{Answer, Solution};
 
-    use super::{get_all_bus_ids, get_bus_times_for_id};
+    fn schedule(timestamp: &str, buses: &str) -> BusSchedule {
+        BusSchedule::parse(&[timestamp.to_string(), buses.to_string()]).unwrap()
+    }
 
     #[test]
     fn test_get_part_1() {
-        let earliest_timestamp = 939;
-        let input = "7,13,x,x,59,x,31,19";
+        let lines = vec![String::from("939"), String::from("7,13,x,x,59,x,31,19")];
 
-        let bus_ids = get_all_bus_ids(input);
+        assert_eq!(295, part1(&lines));
+    }
+
+    #[test]
+    fn test_part_2_examples() {
+        let examples = [
+            ("7,13,x,x,59,x,31,19", 1068781),
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ];
 
-        println!("all bus times: {:?}", bus_ids);
+        for (buses, expected) in examples.iter() {
+            assert_eq!(
+                schedule("939", buses).earliest_aligned_timestamp(),
+                Some(*expected),
+                "{}",
+                buses
+            );
+        }
+    }
 
-        let mut closest_bus_id = 0;
-        let mut closest_diff = MAX;
-        for bus_id in bus_ids.iter() {
-            let bus_id_u32 = bus_id.parse::<u32>().unwrap();
-            let curr_bus_earliest = get_bus_times_for_id(bus_id_u32, earliest_timestamp)
-                .iter()
-                .max()
-                .unwrap()
-                .clone();
+    #[test]
+    fn test_aligned_timestamp_with_shared_factors() {
+        // t ≡ 0 (mod 4) and t + 2 ≡ 0 (mod 6)
+        assert_eq!(schedule("0", "4,x,6").earliest_aligned_timestamp(), Some(4));
 
-            let curr_diff = curr_bus_earliest - earliest_timestamp;
+        // t ≡ 0 (mod 4) and t + 1 ≡ 0 (mod 6) can never happen, t would be even and odd
+        assert_eq!(schedule("0", "4,6").earliest_aligned_timestamp(), None);
+        assert_eq!(
+            Day13::part2(&schedule("939", "4,6")),
+            Answer::from("the buses never line up")
+        );
+    }
 
-            if curr_diff < closest_diff {
-                closest_diff = curr_diff;
-                closest_bus_id = bus_id_u32;
-            }
+    #[test]
+    fn test_parse_keeps_offsets() {
+        assert_eq!(
+            schedule("939", "17,x,13,19").buses(),
+            &[
+                Bus { offset: 0, id: 17 },
+                Bus { offset: 2, id: 13 },
+                Bus { offset: 3, id: 19 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_departure_and_wait_times() {
+        let schedule = schedule("939", "7,13,x,x,59,x,31,19");
+
+        assert_eq!(schedule.next_departure(59, 939), Some(944));
+        assert_eq!(schedule.next_departure(7, 945), Some(945));
+        assert_eq!(schedule.next_departure(8, 939), None);
+        assert_eq!(
+            schedule.wait_times(),
+            vec![(7, 6), (13, 10), (59, 5), (31, 22), (19, 11)]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (timestamp, buses, line_number) in [
+            ("abc", "7,13", 1),
+            ("939", "7,y", 2),
+            ("939", "7,0", 2),
+            ("939", "x,x", 2),
+        ] {
+            let lines = vec![timestamp.to_string(), buses.to_string()];
+            let error = BusSchedule::parse(&lines).unwrap_err();
+            assert_eq!(error.line_number, line_number, "{} {}", timestamp, buses);
         }
 
-        assert_eq!(295, closest_bus_id * closest_diff);
+        assert!(BusSchedule::parse(&["939".to_string()]).is_err());
     }
 }
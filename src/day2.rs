@@ -1,85 +1,534 @@
-use lazy_static::lazy_static;
-use regex::{Captures, Regex};
+use std::{collections::HashSet, error::Error, fmt, str::FromStr};
+
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<PasswordEntry>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        PolicyReport::new(input, PolicyKind::CountRange)
+            .valid
+            .into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        PolicyReport::new(input, PolicyKind::ExactlyOnePosition)
+            .valid
+            .into()
+    }
+}
 
 /// the password is in the format:
 /// number,-,number,space,letter,colon,space,password
 /// ex: 1-3 a: abcde
 /// which means a valid password must have 1-3 as inside it
-pub fn part1(password_lines: &[String]) -> u32 {
-    let mut valid_passwords = Vec::new();
-
-    for password_line in password_lines.iter() {
-        // get the password components
-        let (min, max, c, password) = parse_password_line_parts(password_line);
-
-        // now lets see how many times the character appears in the password
-        let num_appear: i32 = password
-            .chars()
-            .map(|curr_char| {
-                if curr_char == c {
-                    return 1;
-                }
-                0
-            })
-            .sum();
+pub fn part1(password_lines: &[String]) -> usize {
+    let entries: Vec<PasswordEntry> = input_utils::parse_all_as(password_lines).unwrap();
+
+    PolicyReport::new(&entries, PolicyKind::CountRange).valid
+}
+
+/// same format as part 1, but the numbers are 1-based positions and the letter must be at
+/// exactly one of them
+pub fn part2(password_lines: &[String]) -> usize {
+    let entries: Vec<PasswordEntry> = input_utils::parse_all_as(password_lines).unwrap();
+
+    PolicyReport::new(&entries, PolicyKind::ExactlyOnePosition).valid
+}
 
-        if min <= num_appear && num_appear <= max {
-            valid_passwords.push(password_line.clone());
+/// How the numbers and letter of a [`PasswordPolicy`] are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    /// the letter appears at least `first` and at most `second` times (the sled rental policy)
+    CountRange,
+    /// the letter is at exactly one of the 1-based positions `first` and `second` (the
+    /// toboggan rental policy)
+    ExactlyOnePosition,
+    /// the letter is at neither of the 1-based positions `first` and `second`
+    NoneOfPositions,
+    /// the password has at least this many different characters, whatever the policy says
+    MinDistinctChars(usize),
+}
+
+impl fmt::Display for PolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyKind::CountRange => f.write_str("count-range"),
+            PolicyKind::ExactlyOnePosition => f.write_str("exactly-one-position"),
+            PolicyKind::NoneOfPositions => f.write_str("none-of-positions"),
+            PolicyKind::MinDistinctChars(n) => write!(f, "min-distinct-chars-{}", n),
         }
     }
+}
 
-    valid_passwords.len() as u32
+/// The `1-3 a` part of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub first: usize,
+    pub second: usize,
+    pub letter: char,
 }
 
-fn parse_password_line_parts(password_line: &str) -> (i32, i32, char, &str) {
-    lazy_static! {
-        static ref REGEX: Regex = Regex::new(r"(\d+)-(\d+) ([a-z]): ([a-z]+)").unwrap();
+impl fmt::Display for PasswordPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}", self.first, self.second, self.letter)
     }
+}
 
-    let cap: Captures = REGEX.captures(password_line).unwrap();
+/// Why a password does not meet a policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    CountOutOfRange {
+        count: usize,
+        min: usize,
+        max: usize,
+    },
+    /// the position is 0 or past the end of the password
+    PositionOutOfRange {
+        position: usize,
+        length: usize,
+    },
+    LetterAtBothPositions,
+    LetterAtNeitherPosition,
+    LetterAtPosition {
+        position: usize,
+    },
+    TooFewDistinctChars {
+        distinct: usize,
+        required: usize,
+    },
+}
 
-    let min = cap.get(1).unwrap().as_str().parse::<i32>().unwrap();
-    let max = cap.get(2).unwrap().as_str().parse::<i32>().unwrap();
-    let char = cap.get(3).unwrap().as_str().parse::<char>().unwrap();
-    let password = cap.get(4).unwrap().as_str();
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::CountOutOfRange { count, min, max } => write!(
+                f,
+                "letter appears {} times, expected {} to {}",
+                count, min, max
+            ),
+            PolicyViolation::PositionOutOfRange { position, length } => write!(
+                f,
+                "position {} is outside a password of length {}",
+                position, length
+            ),
+            PolicyViolation::LetterAtBothPositions => f.write_str("letter is at both positions"),
+            PolicyViolation::LetterAtNeitherPosition => {
+                f.write_str("letter is at neither position")
+            }
+            PolicyViolation::LetterAtPosition { position } => {
+                write!(f, "letter is at position {}", position)
+            }
+            PolicyViolation::TooFewDistinctChars { distinct, required } => write!(
+                f,
+                "password has {} distinct characters, expected at least {}",
+                distinct, required
+            ),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn check(&self, kind: PolicyKind, password: &str) -> Result<(), PolicyViolation> {
+        match kind {
+            PolicyKind::CountRange => {
+                let count = password.chars().filter(|c| *c == self.letter).count();
+
+                if count < self.first || count > self.second {
+                    return Err(PolicyViolation::CountOutOfRange {
+                        count,
+                        min: self.first,
+                        max: self.second,
+                    });
+                }
+            }
+            PolicyKind::ExactlyOnePosition => {
+                match self.letter_at_positions(password)? {
+                    (true, true) => return Err(PolicyViolation::LetterAtBothPositions),
+                    (false, false) => return Err(PolicyViolation::LetterAtNeitherPosition),
+                    _ => {}
+                };
+            }
+            PolicyKind::NoneOfPositions => {
+                match self.letter_at_positions(password)? {
+                    (true, _) => {
+                        return Err(PolicyViolation::LetterAtPosition {
+                            position: self.first,
+                        })
+                    }
+                    (_, true) => {
+                        return Err(PolicyViolation::LetterAtPosition {
+                            position: self.second,
+                        })
+                    }
+                    _ => {}
+                };
+            }
+            PolicyKind::MinDistinctChars(required) => {
+                let distinct = password.chars().collect::<HashSet<char>>().len();
+
+                if distinct < required {
+                    return Err(PolicyViolation::TooFewDistinctChars { distinct, required });
+                }
+            }
+        };
+
+        Ok(())
+    }
 
-    (min, max, char, password)
+    /// whether the letter is at the first and second 1-based positions
+    fn letter_at_positions(&self, password: &str) -> Result<(bool, bool), PolicyViolation> {
+        let chars: Vec<char> = password.chars().collect();
+
+        let letter_at = |position: usize| match position.checked_sub(1).and_then(|i| chars.get(i)) {
+            Some(c) => Ok(*c == self.letter),
+            None => Err(PolicyViolation::PositionOutOfRange {
+                position,
+                length: chars.len(),
+            }),
+        };
+
+        Ok((letter_at(self.first)?, letter_at(self.second)?))
+    }
 }
 
-pub fn part2(password_lines: &[String]) -> u32 {
-    let mut valid_passwords = Vec::new();
+/// A line of the password database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordEntry {
+    pub policy: PasswordPolicy,
+    pub password: String,
+}
+
+impl PasswordEntry {
+    pub fn check(&self, kind: PolicyKind) -> Result<(), PolicyViolation> {
+        self.policy.check(kind, &self.password)
+    }
+}
+
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.policy, self.password)
+    }
+}
+
+impl FromStr for PasswordEntry {
+    type Err = PasswordEntryError;
+
+    /// parses entries like `1-3 a: abcde`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (policy, password) = s
+            .trim()
+            .split_once(':')
+            .ok_or(PasswordEntryError::MissingPassword)?;
+        let password = password.trim();
+        if password.is_empty() {
+            return Err(PasswordEntryError::MissingPassword);
+        }
+
+        let (range, letter) = policy
+            .split_once(' ')
+            .ok_or(PasswordEntryError::MissingLetter)?;
+        let letter = letter.trim();
+        let mut chars = letter.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (None, _) => return Err(PasswordEntryError::MissingLetter),
+            (Some(c), None) => c,
+            (Some(_), Some(_)) => {
+                return Err(PasswordEntryError::BadLetter {
+                    letter: letter.to_string(),
+                })
+            }
+        };
 
-    for password_line in password_lines.iter() {
-        // get the password components
-        let (first_position, second_position, c, password) =
-            parse_password_line_parts(password_line);
+        let bad_range = || PasswordEntryError::BadRange {
+            range: range.to_string(),
+        };
+        let (first, second) = range.split_once('-').ok_or_else(bad_range)?;
 
-        let mut num_matches = 0;
+        Ok(Self {
+            policy: PasswordPolicy {
+                first: first.parse().map_err(|_| bad_range())?,
+                second: second.parse().map_err(|_| bad_range())?,
+                letter,
+            },
+            password: password.to_string(),
+        })
+    }
+}
+
+/// Why a line of the password database could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordEntryError {
+    /// the policy does not start with two numbers like `1-3`
+    BadRange { range: String },
+    /// there is no letter between the numbers and the colon
+    MissingLetter,
+    /// the policy letter is more than one character
+    BadLetter { letter: String },
+    /// there is nothing after the colon, or no colon at all
+    MissingPassword,
+}
 
-        // check if the first position matches!
-        if password.chars().nth((first_position - 1) as usize).unwrap() == c {
-            // yep!
-            num_matches += 1;
+impl fmt::Display for PasswordEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordEntryError::BadRange { range } => {
+                write!(f, "{:?} is not a range of two numbers like \"1-3\"", range)
+            }
+            PasswordEntryError::MissingLetter => f.write_str("the policy has no letter"),
+            PasswordEntryError::BadLetter { letter } => {
+                write!(f, "policy letter {:?} is not a single character", letter)
+            }
+            PasswordEntryError::MissingPassword => f.write_str("there is no password after \": \""),
         }
+    }
+}
+
+impl Error for PasswordEntryError {}
+
+/// A password that does not meet a policy, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyFailure {
+    /// 1-based line in the database
+    pub line_number: usize,
+    pub entry: PasswordEntry,
+    pub reason: PolicyViolation,
+}
+
+impl fmt::Display for PolicyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {:?}: {}",
+            self.line_number,
+            self.entry.to_string(),
+            self.reason
+        )
+    }
+}
 
-        // check if the second position matches!
-        if password
-            .chars()
-            .nth((second_position - 1) as usize)
-            .unwrap()
-            == c
-        {
-            // yep!
-            num_matches += 1;
+/// The result of checking a whole database against one kind of policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub kind: PolicyKind,
+    pub valid: usize,
+    pub failures: Vec<PolicyFailure>,
+}
+
+impl PolicyReport {
+    pub fn new(entries: &[PasswordEntry], kind: PolicyKind) -> Self {
+        let mut valid = 0;
+        let mut failures = Vec::new();
+
+        for (i, entry) in entries.iter().enumerate() {
+            match entry.check(kind) {
+                Ok(()) => valid += 1,
+                Err(reason) => failures.push(PolicyFailure {
+                    line_number: i + 1,
+                    entry: entry.clone(),
+                    reason,
+                }),
+            }
         }
 
-        // only count it if there was only a single match!
-        if num_matches == 1 {
-            valid_passwords.push(password_line.clone());
+        Self {
+            kind,
+            valid,
+            failures,
         }
+    }
+}
+
+/// the policy kinds an audit checks by default
+pub const AUDIT_POLICY_KINDS: [PolicyKind; 3] = [
+    PolicyKind::CountRange,
+    PolicyKind::ExactlyOnePosition,
+    PolicyKind::NoneOfPositions,
+];
+
+/// A line of the database with its verdict under every audited policy kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditedEntry {
+    /// 1-based line in the database
+    pub line_number: usize,
+    pub entry: PasswordEntry,
+    /// in the same order as the kinds that were audited
+    pub verdicts: Vec<(PolicyKind, Result<(), PolicyViolation>)>,
+}
+
+/// Checks every entry against every kind of policy, so the offending entries can be reviewed
+pub fn audit(entries: &[PasswordEntry], kinds: &[PolicyKind]) -> Vec<AuditedEntry> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| AuditedEntry {
+            line_number: i + 1,
+            entry: entry.clone(),
+            verdicts: kinds
+                .iter()
+                .map(|kind| (*kind, entry.check(*kind)))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        audit, part1, part2, PasswordEntry, PasswordEntryError, PolicyKind, PolicyReport,
+        PolicyViolation, AUDIT_POLICY_KINDS,
+    };
+    use crate::input_utils;
+
+    const INPUT: &str = "1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc";
 
-        // otherwise not valid :<
+    fn entries(input: &str) -> Vec<PasswordEntry> {
+        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+        input_utils::parse_all_as(&lines).unwrap()
     }
 
-    valid_passwords.len() as u32
+    #[test]
+    fn part1_test() {
+        let lines: Vec<String> = INPUT.lines().map(|line| line.to_string()).collect();
+
+        assert_eq!(part1(&lines), 2);
+    }
+
+    #[test]
+    fn part2_test() {
+        let lines: Vec<String> = INPUT.lines().map(|line| line.to_string()).collect();
+
+        assert_eq!(part2(&lines), 1);
+    }
+
+    #[test]
+    fn report_lists_failures() {
+        let report = PolicyReport::new(&entries(INPUT), PolicyKind::ExactlyOnePosition);
+
+        assert_eq!(report.valid, 1);
+        let reasons: Vec<(usize, PolicyViolation)> = report
+            .failures
+            .iter()
+            .map(|failure| (failure.line_number, failure.reason.clone()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, PolicyViolation::LetterAtNeitherPosition),
+                (3, PolicyViolation::LetterAtBothPositions)
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_outside_the_password_are_violations() {
+        let entries = entries("0-1 a: abc\n1-9 a: abc");
+
+        assert_eq!(
+            entries[0].check(PolicyKind::ExactlyOnePosition),
+            Err(PolicyViolation::PositionOutOfRange {
+                position: 0,
+                length: 3
+            })
+        );
+        assert_eq!(
+            entries[1].check(PolicyKind::NoneOfPositions),
+            Err(PolicyViolation::PositionOutOfRange {
+                position: 9,
+                length: 3
+            })
+        );
+        // a count range starting at 0 is fine
+        assert_eq!(entries[0].check(PolicyKind::CountRange), Ok(()));
+    }
+
+    #[test]
+    fn other_policy_kinds() {
+        let entries = entries(INPUT);
+
+        assert_eq!(entries[1].check(PolicyKind::NoneOfPositions), Ok(()));
+        assert_eq!(
+            entries[0].check(PolicyKind::NoneOfPositions),
+            Err(PolicyViolation::LetterAtPosition { position: 1 })
+        );
+        assert_eq!(entries[0].check(PolicyKind::MinDistinctChars(5)), Ok(()));
+        assert_eq!(
+            entries[2].check(PolicyKind::MinDistinctChars(2)),
+            Err(PolicyViolation::TooFewDistinctChars {
+                distinct: 1,
+                required: 2
+            })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |s: &str| s.parse::<PasswordEntry>().unwrap_err();
+
+        assert_eq!(error("1-3 a abcde"), PasswordEntryError::MissingPassword);
+        assert_eq!(error("1-3 a: "), PasswordEntryError::MissingPassword);
+        assert_eq!(error("1-3: abcde"), PasswordEntryError::MissingLetter);
+        assert_eq!(error("1-3 : abcde"), PasswordEntryError::MissingLetter);
+        assert_eq!(
+            error("1-3 ab: abcde"),
+            PasswordEntryError::BadLetter {
+                letter: "ab".to_string()
+            }
+        );
+        assert_eq!(
+            error("a-3 a: abcde"),
+            PasswordEntryError::BadRange {
+                range: "a-3".to_string()
+            }
+        );
+        assert_eq!(
+            error("13 a: abcde"),
+            PasswordEntryError::BadRange {
+                range: "13".to_string()
+            }
+        );
+        assert!(matches!(
+            error("99999999999999999999999-3 a: abcde"),
+            PasswordEntryError::BadRange { .. }
+        ));
+    }
+
+    #[test]
+    fn audit_checks_every_kind() {
+        let audited = audit(&entries(INPUT), &AUDIT_POLICY_KINDS);
+
+        assert_eq!(audited.len(), 3);
+        assert_eq!(audited[1].line_number, 2);
+        assert_eq!(
+            audited[1].verdicts,
+            vec![
+                (
+                    PolicyKind::CountRange,
+                    Err(PolicyViolation::CountOutOfRange {
+                        count: 0,
+                        min: 1,
+                        max: 3
+                    })
+                ),
+                (
+                    PolicyKind::ExactlyOnePosition,
+                    Err(PolicyViolation::LetterAtNeitherPosition)
+                ),
+                (PolicyKind::NoneOfPositions, Ok(())),
+            ]
+        );
+    }
 }
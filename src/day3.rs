@@ -1,4 +1,12 @@
-/// With the toboggan login problems resolved, you set off toward the airport. While travel by toboggan might be easy, it's certainly not safe: there's very minimal steering and the area is covered in trees. You'll need to see which angles will take you near the fewest trees.
+use std::{convert::TryFrom, fmt};
+
+use crate::{
+    grid::Grid,
+    input_utils::LineError,
+    solution::{Answer, Solution},
+};
+
+// With the toboggan login problems resolved, you set off toward the airport. While travel by toboggan might be easy, it's certainly not safe: there's very minimal steering and the area is covered in trees. You'll need to see which angles will take you near the fewest trees.
 
 // Due to the local geology, trees in this area only grow on exact integer coordinates in a grid. You make a map (your puzzle input) of the open squares (.) and trees (#) you can see. For example:
 
@@ -52,62 +60,337 @@
 
 // Starting at the top-left corner of your map and following a slope of right 3 and down 1, how many trees would you encounter?
 
-pub fn part1(input: &[String]) -> u32 {
-    // first we need to parse the input into an array of arrays
-    let tree_map: Vec<Vec<bool>> = input.iter().map(|line| parse_line(line)).collect();
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input = Grid<Square>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        Grid::parse(input)
+    }
 
-    // println!("map");
-    // let mut index: usize = 0;
-    // for line in input.iter() {
-    //     println!("{:?} | {:?}", line, tree_map[index]);
-    //     index += 1;
-    // }
+    fn part1(input: &Self::Input) -> Answer {
+        trace(input, PART1_SLOPE).trees.into()
+    }
 
-    calculate_trees_hit(&tree_map, 3, 1)
+    fn part2(input: &Self::Input) -> Answer {
+        slopes_product(input).into()
+    }
 }
 
-pub fn part2(input: &[String]) -> u32 {
-    // first we need to parse the input into an array of arrays
-    let tree_map: Vec<Vec<bool>> = input.iter().map(|line| parse_line(line)).collect();
+/// right 3, down 1
+pub const PART1_SLOPE: Slope = Slope { right: 3, down: 1 };
 
-    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+/// the slopes checked in part 2
+pub const PART2_SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
 
-    slopes
+pub fn part1(input: &[String]) -> usize {
+    let tree_map: Grid<Square> = Grid::parse(input).unwrap();
+
+    trace(&tree_map, PART1_SLOPE).trees
+}
+
+pub fn part2(input: &[String]) -> usize {
+    let tree_map: Grid<Square> = Grid::parse(input).unwrap();
+
+    slopes_product(&tree_map)
+}
+
+fn slopes_product(tree_map: &Grid<Square>) -> usize {
+    analyse(tree_map, &PART2_SLOPES)
         .iter()
-        .map(|(x, y)| calculate_trees_hit(&tree_map, x.clone() as usize, y.clone() as usize))
+        .map(|path| path.trees)
         .product()
 }
 
-fn calculate_trees_hit(grid: &Vec<Vec<bool>>, x_slope: usize, y_slope: usize) -> u32 {
-    assert!(grid.len() >= 1);
-    let height = grid.len();
-    let width = grid[0].len();
-    let mut x = 0;
-    let mut y = 0;
+/// How far the toboggan moves each step. `down` must be at least 1, or the toboggan would never
+/// reach the bottom of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: usize,
+    pub down: usize,
+}
 
-    let mut tree_count = 0;
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "right {}, down {}", self.right, self.down)
+    }
+}
 
-    while y < height {
-        // check to see if we are at a tree
-        if grid[y][x] {
-            tree_count += 1;
-        }
+/// The squares visited following a slope from the top-left corner until past the bottom of the
+/// map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub slope: Slope,
+    /// `(row, column)` of every visited square, starting with `(0, 0)`. Columns keep counting
+    /// past the right edge of the map, as if it was repeated.
+    pub visited: Vec<(usize, usize)>,
+    /// how many of the visited squares are trees
+    pub trees: usize,
+}
+
+/// Follows `slope` down the map, recording every square visited on the way
+pub fn trace(tree_map: &Grid<Square>, slope: Slope) -> Path {
+    assert!(slope.down > 0, "a slope must go down");
+
+    let visited: Vec<(usize, usize)> = (0..tree_map.height())
+        .step_by(slope.down)
+        .enumerate()
+        .map(|(step, row)| (row, step * slope.right))
+        .collect();
+
+    let trees = visited
+        .iter()
+        .filter(|(row, column)| {
+            *tree_map.get_wrapping(*row as isize, *column as isize) == Square::Tree
+        })
+        .count();
+
+    Path {
+        slope,
+        visited,
+        trees,
+    }
+}
+
+/// Traces every slope, in the same order
+pub fn analyse(tree_map: &Grid<Square>, slopes: &[Slope]) -> Vec<Path> {
+    slopes.iter().map(|slope| trace(tree_map, *slope)).collect()
+}
+
+/// Tries every slope up to `max_right` and `max_down`, and returns the path hitting the fewest
+/// trees. Ties go to the slope with the smallest `down`, then the smallest `right`. Returns
+/// `None` if `max_down` is 0.
+pub fn best_slope(tree_map: &Grid<Square>, max_right: usize, max_down: usize) -> Option<Path> {
+    (1..=max_down)
+        .flat_map(|down| (0..=max_right).map(move |right| Slope { right, down }))
+        .map(|slope| trace(tree_map, slope))
+        .min_by_key(|path| path.trees)
+}
+
+/// How [`render_path`] draws the map
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// how many copies of the map to draw side by side. More are drawn if the path needs them.
+    pub tiles: Option<usize>,
+    /// colour the visited squares using ANSI escape codes
+    pub colour: bool,
+}
+
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RED: &str = "\x1b[1;31m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Draws the map repeated to the right, the way the puzzle does, with the visited squares
+/// marked `O` when open and `X` when there was a tree. As in the puzzle, the starting square is
+/// left unmarked and every fifth row ends with an arrow.
+pub fn render_path(tree_map: &Grid<Square>, path: &Path, options: RenderOptions) -> String {
+    let width = tree_map.width();
+    let needed_tiles = path
+        .visited
+        .iter()
+        .map(|(_, column)| column / width.max(1) + 1)
+        .max()
+        .unwrap_or(1);
+    let tiles = options.tiles.unwrap_or(1).max(needed_tiles);
+
+    let mut visited = path.visited.iter().skip(1).peekable();
+    let mut result = String::new();
+
+    for row in 0..tree_map.height() {
+        let marked_column = match visited.peek() {
+            Some((visited_row, column)) if *visited_row == row => {
+                visited.next();
+                Some(*column)
+            }
+            _ => None,
+        };
 
-        // now lets get the new slope
+        for column in 0..(width * tiles) {
+            let square = *tree_map.get_wrapping(row as isize, column as isize);
 
-        x = (x + x_slope) % width;
-        y += y_slope;
+            if marked_column != Some(column) {
+                result.push_str(&square.to_string());
+                continue;
+            }
+
+            let (mark, colour) = match square {
+                Square::Open => ('O', ANSI_GREEN),
+                Square::Tree => ('X', ANSI_RED),
+            };
+            if options.colour {
+                result.push_str(&format!("{}{}{}", colour, mark, ANSI_RESET));
+            } else {
+                result.push(mark);
+            }
+        }
+
+        if row % 5 == 0 {
+            result.push_str("  --->");
+        }
+        result.push('\n');
     }
 
-    tree_count
+    result
+}
+
+/// A square on the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Square {
+    Open,
+    Tree,
 }
 
-fn parse_line(line: &str) -> Vec<bool> {
-    line.chars()
-        .map(|c| match c {
-            '.' => false,
-            '#' => true,
-            _ => panic!("illegale input"),
+impl TryFrom<char> for Square {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Square::Open),
+            '#' => Ok(Square::Tree),
+            v => Err(format!("invalid square: {:?}. Expected one of [., #]", v)),
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Square::Open => ".",
+            Square::Tree => "#",
         })
-        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        analyse, best_slope, part1, part2, render_path, trace, RenderOptions, Slope, Square,
+        PART2_SLOPES,
+    };
+    use crate::grid::Grid;
+
+    const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn example() {
+        let lines: Vec<String> = EXAMPLE.lines().map(|line| line.to_string()).collect();
+
+        assert_eq!(part1(&lines), 7);
+        assert_eq!(part2(&lines), 336);
+    }
+
+    fn tree_map() -> Grid<Square> {
+        let lines: Vec<String> = EXAMPLE.lines().map(|line| line.to_string()).collect();
+        Grid::parse(&lines).unwrap()
+    }
+
+    #[test]
+    fn trace_records_the_path() {
+        let path = trace(&tree_map(), Slope { right: 1, down: 2 });
+
+        assert_eq!(
+            path.visited,
+            vec![(0, 0), (2, 1), (4, 2), (6, 3), (8, 4), (10, 5)]
+        );
+        assert_eq!(path.trees, 2);
+
+        // columns are not wrapped, so the path can be drawn over the repeated map
+        let path = trace(&tree_map(), Slope { right: 3, down: 1 });
+        assert_eq!(path.visited.last(), Some(&(10, 30)));
+    }
+
+    #[test]
+    fn analyse_keeps_the_slope_order() {
+        let trees: Vec<usize> = analyse(&tree_map(), &PART2_SLOPES)
+            .iter()
+            .map(|path| path.trees)
+            .collect();
+
+        assert_eq!(trees, vec![2, 7, 3, 4, 2]);
+    }
+
+    #[test]
+    fn best_slope_finds_the_fewest_trees() {
+        let best = best_slope(&tree_map(), 7, 2).unwrap();
+
+        assert_eq!(best.slope, Slope { right: 5, down: 2 });
+        assert_eq!(best.trees, 0);
+        assert_eq!(
+            best_slope(&tree_map(), 7, 1).unwrap().slope,
+            Slope { right: 2, down: 1 }
+        );
+        assert_eq!(best_slope(&tree_map(), 7, 0), None);
+    }
+
+    #[test]
+    fn render_matches_the_puzzle() {
+        let expected = "..##.........##.........##.........##.........##.........##.......  --->
+#..O#...#..#...#...#..#...#...#..#...#...#..#...#...#..#...#...#..
+.#....X..#..#....#..#..#....#..#..#....#..#..#....#..#..#....#..#.
+..#.#...#O#..#.#...#.#..#.#...#.#..#.#...#.#..#.#...#.#..#.#...#.#
+.#...##..#..X...##..#..#...##..#..#...##..#..#...##..#..#...##..#.
+..#.##.......#.X#.......#.##.......#.##.......#.##.......#.##.....  --->
+.#.#.#....#.#.#.#.O..#.#.#.#....#.#.#.#....#.#.#.#....#.#.#.#....#
+.#........#.#........X.#........#.#........#.#........#.#........#
+#.##...#...#.##...#...#.X#...#...#.##...#...#.##...#...#.##...#...
+#...##....##...##....##...#X....##...##....##...##....##...##....#
+.#..#...#.#.#..#...#.#.#..#...X.#.#..#...#.#.#..#...#.#.#..#...#.#  --->
+";
+
+        let tree_map = tree_map();
+        let path = trace(&tree_map, Slope { right: 3, down: 1 });
+
+        let rendered = render_path(
+            &tree_map,
+            &path,
+            RenderOptions {
+                tiles: Some(6),
+                colour: false,
+            },
+        );
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn render_tiles_enough_for_the_path() {
+        let tree_map = tree_map();
+        let path = trace(&tree_map, Slope { right: 7, down: 1 });
+
+        let rendered = render_path(&tree_map, &path, RenderOptions::default());
+        let first_line = rendered.lines().next().unwrap();
+        assert_eq!(first_line.len(), 7 * 11 + "  --->".len());
+
+        let coloured = render_path(
+            &tree_map,
+            &path,
+            RenderOptions {
+                tiles: None,
+                colour: true,
+            },
+        );
+        assert!(coloured
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("#...#..\x1b[32mO\x1b[0m"));
+    }
 }
@@ -1,206 +1,540 @@
+use std::{convert::TryFrom, fmt, io, str::FromStr};
+
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashMap;
-
-use maplit::hashmap;
-
-#[derive(Debug)]
-struct Passport {
-    birth_year: Option<u16>,
-    issue_year: Option<u16>,
-    expiration_year: Option<u16>,
-    height: Option<String>,
-    hair_colour: Option<String>,
-    eye_colour: Option<String>,
-    passport_id: Option<String>,
-    country_id: Option<String>,
-}
-
-impl Passport {
-    fn new(lines: &[String]) -> Self {
-        let mut passport_fields: HashMap<&str, &str> = HashMap::new();
-
-        for line in lines.iter() {
-            line.split_whitespace()
-                .map(|s| s.split_at(s.find(":").unwrap()))
-                .for_each(|(k, v)| {
-                    assert!(passport_fields.insert(k, &v[1..]).is_none());
-                });
-        }
 
-        Passport {
-            birth_year: passport_fields
-                .get("byr")
-                .map(|y| y.parse::<u16>().unwrap()),
-            issue_year: passport_fields
-                .get("iyr")
-                .map(|y| y.parse::<u16>().unwrap()),
-            expiration_year: passport_fields
-                .get("eyr")
-                .map(|y| y.parse::<u16>().unwrap()),
-            height: passport_fields.get("hgt").map(|s| s.to_string()),
-            hair_colour: passport_fields.get("hcl").map(|s| s.to_string()),
-            eye_colour: passport_fields.get("ecl").map(|s| s.to_string()),
-            passport_id: passport_fields.get("pid").map(|s| s.to_string()),
-            country_id: passport_fields.get("cid").map(|s| s.to_string()),
-        }
+use crate::{
+    input_utils::{self, DuplicateKeys, LineError, Record},
+    schema::{FieldViolation, Rule, Schema},
+    solution::{Answer, Solution},
+};
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Input = Vec<Record>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        parse_passports(input)
     }
 
-    fn validate(&self) -> bool {
-        if self.birth_year.is_none() {
-            return false;
-        }
+    fn part1(input: &Self::Input) -> Answer {
+        count_valid(input, false).into()
+    }
 
-        if self.issue_year.is_none() {
-            return false;
-        }
+    fn part2(input: &Self::Input) -> Answer {
+        count_valid(input, true).into()
+    }
+}
 
-        if self.expiration_year.is_none() {
-            return false;
-        }
+/// The passport rules from part 2, which also work as a starting point for other documents
+pub fn passport_schema() -> Schema {
+    Schema::new()
+        .field("byr", true, Rule::IntRange(1920..=2002))
+        .field("iyr", true, Rule::IntRange(2010..=2020))
+        .field("eyr", true, Rule::IntRange(2020..=2030))
+        .field(
+            "hgt",
+            true,
+            Rule::UnitRange(vec![
+                (String::from("cm"), 150..=193),
+                (String::from("in"), 59..=76),
+            ]),
+        )
+        .field(
+            "hcl",
+            true,
+            Rule::Regex(Regex::new(r"^#[0-9a-f]{6}$").unwrap()),
+        )
+        .field(
+            "ecl",
+            true,
+            Rule::OneOf(
+                ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            ),
+        )
+        .field("pid", true, Rule::Digits(9))
+        // the North Pole Credentials we have don't have a country id
+        .field("cid", false, Rule::Any)
+}
 
-        if self.height.is_none() {
-            return false;
-        }
+/// Every passport in a batch file. A passport with the same field twice is rejected.
+pub fn parse_passports(input: &[String]) -> Result<Vec<Record>, LineError> {
+    input_utils::parse_records(input, DuplicateKeys::Reject)
+}
 
-        if self.hair_colour.is_none() {
-            return false;
-        }
+/// the required fields that are missing
+fn validate(passport: &Record, schema: &Schema) -> Vec<FieldViolation> {
+    schema.missing_fields(passport.fields())
+}
 
-        if self.eye_colour.is_none() {
-            return false;
-        }
+/// every field that is missing or has an invalid value
+fn validate_strict(passport: &Record, schema: &Schema) -> Vec<FieldViolation> {
+    schema.validate(passport.fields())
+}
 
-        if self.passport_id.is_none() {
-            return false;
-        }
+lazy_static! {
+    static ref PASSPORT_SCHEMA: Schema = passport_schema();
+}
 
-        // ignore for now
-        // if self.country_id.is_none() {
-        //     return false
-        // }
+/// A passport that follows every rule of [`passport_schema`], with its values parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_colour: HairColour,
+    pub eye_colour: EyeColour,
+    /// nine digits, keeping any leading zeroes
+    pub passport_id: String,
+    pub country_id: Option<String>,
+}
 
-        true
-    }
+impl TryFrom<&Record> for Passport {
+    type Error = Vec<FieldViolation>;
 
-    fn validate_strict(&self) -> bool {
-        if !self.validate() {
-            return false;
+    /// fails with every field breaking the part 2 rules
+    fn try_from(record: &Record) -> Result<Self, Self::Error> {
+        let violations = PASSPORT_SCHEMA.validate(record.fields());
+        if !violations.is_empty() {
+            return Err(violations);
         }
 
-        // we know that all the fields are set, now to validate
+        // every required field is there and valid, so it parses
+        let field = |key: &str| record.get(key).unwrap();
+        let year = |key: &str| field(key).parse::<u16>().unwrap();
+
+        Ok(Passport {
+            birth_year: year("byr"),
+            issue_year: year("iyr"),
+            expiration_year: year("eyr"),
+            height: field("hgt").parse().unwrap(),
+            hair_colour: field("hcl").parse().unwrap(),
+            eye_colour: field("ecl").parse().unwrap(),
+            passport_id: field("pid").to_string(),
+            country_id: record.get("cid").map(|cid| cid.to_string()),
+        })
+    }
+}
 
-        if self.birth_year.unwrap() < 1920 || self.birth_year.unwrap() > 2002 {
-            return false;
-        }
+/// `(line_number, passport)` for every passport that follows the part 2 rules
+pub fn valid_passports(passports: &[Record]) -> Vec<(usize, Passport)> {
+    passports
+        .iter()
+        .filter_map(|record| Some((record.line_number, Passport::try_from(record).ok()?)))
+        .collect()
+}
 
-        if self.issue_year.unwrap() < 2010 || self.issue_year.unwrap() > 2020 {
-            return false;
-        }
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
 
-        if self.expiration_year.unwrap() < 2020 || self.expiration_year.unwrap() > 2030 {
-            return false;
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightUnit {
+    Centimetres,
+    Inches,
+}
+
+impl fmt::Display for HeightUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HeightUnit::Centimetres => "cm",
+            HeightUnit::Inches => "in",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Height {
+    pub value: u32,
+    pub unit: HeightUnit,
+}
 
-        // height
-        // check if cm or in present
-        let h = self.height.as_ref().unwrap().clone();
-        let valid_height_range = hashmap! {
-            "cm" => (150, 193),
-            "in" => (59, 76),
+impl FromStr for Height {
+    type Err = io::Error;
+
+    /// a number followed by `cm` or `in`, like `183cm`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = if let Some(value) = s.strip_suffix("cm") {
+            (value, HeightUnit::Centimetres)
+        } else if let Some(value) = s.strip_suffix("in") {
+            (value, HeightUnit::Inches)
+        } else {
+            return Err(invalid_input(format!(
+                "height {:?} should end in cm or in",
+                s
+            )));
         };
 
-        let mut height_type = "";
-        let mut height_value = 0;
+        let value = value
+            .parse::<u32>()
+            .map_err(|e| invalid_input(format!("height {:?} is not a number: {}", s, e)))?;
 
-        if let Some(i) = h.find("cm") {
-            height_type = "cm";
-            height_value = h[..(i)].parse::<u32>().unwrap();
-        }
+        Ok(Height { value, unit })
+    }
+}
 
-        if let Some(i) = h.find("in") {
-            height_type = "in";
-            height_value = h[..(i)].parse::<u32>().unwrap();
-        }
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
 
-        if height_type.is_empty() {
-            return false;
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairColour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
 
-        let (min, max) = valid_height_range[height_type];
-        if height_value < min || height_value > max {
-            return false;
-        }
+impl FromStr for HairColour {
+    type Err = io::Error;
 
-        // hair colour
-        lazy_static! {
-            static ref REGEX: Regex = Regex::new(r"#[0-9a-f]{6}$").unwrap();
-        }
-        if !REGEX.is_match(self.hair_colour.as_ref().unwrap()) {
-            return false;
-        }
+    /// `#` followed by six hex digits, like `#fffffd`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| invalid_input(format!("hair colour {:?} should be like #a1b2c3", s)))?;
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..(i + 2)], 16).unwrap();
 
-        // eye colour
-        let ecl = self.eye_colour.as_ref().unwrap();
-        let valid_eye_colours = vec!["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-        if !valid_eye_colours.contains(&ecl.as_str()) {
-            return false;
+        Ok(HairColour {
+            red: channel(0),
+            green: channel(2),
+            blue: channel(4),
+        })
+    }
+}
+
+impl fmt::Display for HairColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EyeColour {
+    Amber,
+    Blue,
+    Brown,
+    Grey,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl EyeColour {
+    /// the three letter code used in passports
+    pub fn code(&self) -> &'static str {
+        match self {
+            EyeColour::Amber => "amb",
+            EyeColour::Blue => "blu",
+            EyeColour::Brown => "brn",
+            EyeColour::Grey => "gry",
+            EyeColour::Green => "grn",
+            EyeColour::Hazel => "hzl",
+            EyeColour::Other => "oth",
         }
+    }
+}
 
-        if self.passport_id.as_ref().unwrap().len() != 9 {
-            return false;
+impl FromStr for EyeColour {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "amb" => Ok(EyeColour::Amber),
+            "blu" => Ok(EyeColour::Blue),
+            "brn" => Ok(EyeColour::Brown),
+            "gry" => Ok(EyeColour::Grey),
+            "grn" => Ok(EyeColour::Green),
+            "hzl" => Ok(EyeColour::Hazel),
+            "oth" => Ok(EyeColour::Other),
+            v => Err(invalid_input(format!(
+                "invalid eye colour: {:?}. Expected one of [amb, blu, brn, gry, grn, hzl, oth]",
+                v
+            ))),
         }
+    }
+}
 
-        true
+impl fmt::Display for EyeColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
     }
 }
 
-pub fn part1(input: &[String]) -> u32 {
-    // parse for spaces and new lines
-    // if a line is empty, end of passport and validate it
+/// The outcome of checking one passport in a batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportCheck {
+    /// 1-based line the passport starts on
+    pub line_number: usize,
+    pub violations: Vec<FieldViolation>,
+}
 
-    let mut passport_lines = Vec::new();
-    let mut valid_passport_count = 0;
+impl PassportCheck {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
 
-    for line in input.iter() {
-        if line.trim().is_empty() {
-            let passport = Passport::new(&passport_lines);
+/// Checks every passport in a batch file against `schema`. When `strict`, every value must
+/// follow its rule as in part 2, otherwise the required fields only have to be there.
+pub fn check_passports(passports: &[Record], schema: &Schema, strict: bool) -> Vec<PassportCheck> {
+    passports
+        .iter()
+        .map(|passport| {
+            let violations = if strict {
+                validate_strict(passport, schema)
+            } else {
+                validate(passport, schema)
+            };
+
+            PassportCheck {
+                line_number: passport.line_number,
+                violations,
+            }
+        })
+        .collect()
+}
 
-            // println!("{:?}", passport);
+/// How many passports were rejected because of each field, in schema order. Fields that never
+/// failed are left out.
+pub fn failure_counts(checks: &[PassportCheck], schema: &Schema) -> Vec<(String, usize)> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let count = checks
+                .iter()
+                .filter(|check| check.violations.iter().any(|v| v.key == field.key))
+                .count();
+            (field.key.clone(), count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
 
-            if passport.validate() {
-                valid_passport_count += 1;
-            }
+pub fn part1(input: &[String]) -> usize {
+    count_valid(&parse_passports(input).unwrap(), false)
+}
 
-            passport_lines.clear();
-        }
+pub fn part2(input: &[String]) -> usize {
+    count_valid(&parse_passports(input).unwrap(), true)
+}
+
+fn count_valid(passports: &[Record], strict: bool) -> usize {
+    check_passports(passports, &PASSPORT_SCHEMA, strict)
+        .iter()
+        .filter(|check| check.is_valid())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
 
-        passport_lines.push(line.to_owned());
+    use super::{
+        check_passports, failure_counts, parse_passports, part1, part2, passport_schema, EyeColour,
+        HairColour, Height, HeightUnit, Passport,
+    };
+    use crate::schema::Schema;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(|line| line.to_string()).collect()
     }
 
-    valid_passport_count
-}
+    #[test]
+    fn example_part1() {
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
 
-pub fn part2(input: &[String]) -> u32 {
-    // parse for spaces and new lines
-    // if a line is empty, end of passport and validate it
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
 
-    let mut passport_lines = Vec::new();
-    let mut valid_passport_count = 0;
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
 
-    for line in input.iter() {
-        if line.trim().is_empty() {
-            let passport = Passport::new(&passport_lines);
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in";
 
-            if passport.validate_strict() {
-                valid_passport_count += 1;
-            }
+        assert_eq!(part1(&lines(input)), 2);
+    }
 
-            passport_lines.clear();
-        }
+    #[test]
+    fn example_part2() {
+        let invalid = "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007
+
+byr:abc iyr:2010 eyr:2020 hgt:150cm hcl:#000000 ecl:amb pid:000000001";
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
 
-        passport_lines.push(line.to_owned());
+        assert_eq!(part2(&lines(invalid)), 0);
+        assert_eq!(part2(&lines(valid)), 4);
     }
 
-    valid_passport_count
+    #[test]
+    fn checked_in_schema_is_the_default() {
+        assert_eq!(
+            Schema::load("schemas/passport.toml").unwrap(),
+            passport_schema()
+        );
+    }
+
+    #[test]
+    fn check_passports_reports_each_field() {
+        let input = "byr:1919 iyr:2010 eyr:2020 hgt:190in hcl:123abc ecl:amb pid:0123456789
+
+byr:1920 iyr:2010 eyr:2020 hgt:150cm hcl:#000000 ecl:amb
+pid:000000001
+cid:5
+
+
+iyr:2010 eyr:2020";
+
+        let passports = parse_passports(&lines(input)).unwrap();
+        let checks = check_passports(&passports, &passport_schema(), true);
+
+        let reports: Vec<(usize, Vec<String>)> = checks
+            .iter()
+            .map(|check| {
+                let violations = check.violations.iter().map(|v| v.to_string()).collect();
+                (check.line_number, violations)
+            })
+            .collect();
+        assert_eq!(
+            reports,
+            vec![
+                (
+                    1,
+                    vec![
+                        String::from("byr: out of range (1919 < 1920)"),
+                        String::from("hgt: out of range (190in > 76in)"),
+                        String::from("hcl: \"123abc\" does not match ^#[0-9a-f]{6}$"),
+                        String::from("pid: length 10, expected 9"),
+                    ]
+                ),
+                (3, vec![]),
+                (
+                    8,
+                    vec![
+                        String::from("byr: missing"),
+                        String::from("hgt: missing"),
+                        String::from("hcl: missing"),
+                        String::from("ecl: missing"),
+                        String::from("pid: missing"),
+                    ]
+                ),
+            ]
+        );
+
+        let counts = failure_counts(&checks, &passport_schema());
+        assert_eq!(
+            counts,
+            vec![
+                (String::from("byr"), 2),
+                (String::from("hgt"), 2),
+                (String::from("hcl"), 2),
+                (String::from("ecl"), 1),
+                (String::from("pid"), 2),
+            ]
+        );
+
+        let loose = check_passports(&passports, &passport_schema(), false);
+        assert_eq!(loose.iter().filter(|check| check.is_valid()).count(), 2);
+    }
+
+    #[test]
+    fn duplicate_fields_are_rejected() {
+        let error = parse_passports(&lines("byr:1920\n\niyr:2010\niyr:2011")).unwrap_err();
+        assert_eq!(error.line_number, 4);
+    }
+
+    #[test]
+    fn typed_passports() {
+        let passports = parse_passports(&lines(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989 hgt:59cm
+iyr:2014 pid:896056539 hcl:#a97842",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            Passport::try_from(&passports[0]).unwrap(),
+            Passport {
+                birth_year: 1980,
+                issue_year: 2012,
+                expiration_year: 2030,
+                height: Height {
+                    value: 74,
+                    unit: HeightUnit::Inches
+                },
+                hair_colour: HairColour {
+                    red: 0x62,
+                    green: 0x3a,
+                    blue: 0x2f
+                },
+                eye_colour: EyeColour::Green,
+                passport_id: String::from("087499704"),
+                country_id: None,
+            }
+        );
+
+        let violations = Passport::try_from(&passports[1]).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].key, "hgt");
+    }
+
+    #[test]
+    fn typed_fields_round_trip() {
+        for height in ["183cm", "60in"] {
+            assert_eq!(height.parse::<Height>().unwrap().to_string(), height);
+        }
+        assert!("183".parse::<Height>().is_err());
+        assert!("cm".parse::<Height>().is_err());
+
+        assert_eq!(
+            "#0a0B0c".parse::<HairColour>().unwrap().to_string(),
+            "#0a0b0c"
+        );
+        assert!("#0a0b0".parse::<HairColour>().is_err());
+        assert!("0a0b0c0".parse::<HairColour>().is_err());
+
+        assert_eq!("hzl".parse::<EyeColour>().unwrap(), EyeColour::Hazel);
+        assert_eq!(EyeColour::Grey.to_string(), "gry");
+        assert!("wat".parse::<EyeColour>().is_err());
+    }
 }
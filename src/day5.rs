@@ -1,143 +1,527 @@
-use std::str::Chars;
+use std::{error::Error, fmt, str::FromStr};
 
-pub fn part1(input: &[String]) -> u32 {
-    input
-        .iter()
-        .map(|ticket_deets| {
-            let row = calculate_row(&ticket_deets[..7], 128);
-            let column = calculate_column(&ticket_deets[7..], 8);
+use crate::{
+    grid::Grid,
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
 
-            (row * 8) + column
-        })
-        .max()
-        .unwrap()
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Vec<BoardingPass>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        highest_seat_id(input).map_or_else(
+            || Answer::from("there are no boarding passes"),
+            Answer::from,
+        )
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        find_our_seat(input).map_or_else(
+            || Answer::from("there is no free seat between two taken seats"),
+            Answer::from,
+        )
+    }
 }
 
-#[derive(Clone, Debug)]
-struct TicketData {
-    row: u32,
-    column: u32,
-    id: u32,
+pub fn part1(input: &[String]) -> Option<u32> {
+    let passes: Vec<BoardingPass> = input_utils::parse_all_as(input).unwrap();
+
+    highest_seat_id(&passes)
 }
 
-impl Ord for TicketData {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.id.cmp(&other.id)
-    }
+pub fn part2(input: &[String]) -> Option<u32> {
+    let passes: Vec<BoardingPass> = input_utils::parse_all_as(input).unwrap();
+
+    find_our_seat(&passes)
 }
 
-impl PartialOrd for TicketData {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+fn highest_seat_id(passes: &[BoardingPass]) -> Option<u32> {
+    passes.iter().map(|pass| pass.seat_id()).max()
+}
+
+/// our seat is the only one missing where the seats either side of it are taken
+fn find_our_seat(passes: &[BoardingPass]) -> Option<u32> {
+    SeatingChart::new(PlaneLayout::DEFAULT, passes)
+        .free_seats_between_taken()
+        .first()
+        .map(|seat| seat.seat_id())
+}
+
+/// How many rows and columns the plane has, as the number of characters a boarding pass
+/// uses for each. Every extra character doubles the rows or columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlaneLayout {
+    row_bits: u32,
+    column_bits: u32,
 }
 
-impl Eq for TicketData {}
+impl PlaneLayout {
+    /// the plane in the puzzle, 128 rows of 8 seats
+    pub const DEFAULT: PlaneLayout = PlaneLayout {
+        row_bits: 7,
+        column_bits: 3,
+    };
+
+    /// Panics if the plane has more than 2^32 seats, as seat ids would not fit in a `u32`
+    pub fn new(row_bits: u32, column_bits: u32) -> Self {
+        assert!(
+            row_bits + column_bits <= 32,
+            "a plane can have at most 32 bits of seats"
+        );
+
+        Self {
+            row_bits,
+            column_bits,
+        }
+    }
+
+    pub fn row_bits(&self) -> u32 {
+        self.row_bits
+    }
+
+    pub fn column_bits(&self) -> u32 {
+        self.column_bits
+    }
+
+    pub fn rows(&self) -> u64 {
+        1 << self.row_bits
+    }
+
+    pub fn columns(&self) -> u64 {
+        1 << self.column_bits
+    }
 
-impl PartialEq for TicketData {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+    /// how many characters a boarding pass has
+    pub fn pass_length(&self) -> usize {
+        (self.row_bits + self.column_bits) as usize
     }
 }
 
-pub fn part2(input: &[String]) -> u32 {
-    let mut all_ids: Vec<TicketData> = input
-        .iter()
-        .map(|ticket_deets| {
-            let row = calculate_row(&ticket_deets[..7], 128);
-            let column = calculate_column(&ticket_deets[7..], 8);
+impl Default for PlaneLayout {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
 
-            let id = (row * 8) + column;
-            TicketData { row, column, id }
-        })
-        .collect();
+/// A seat on the plane, written as a binary space partitioning code like `FBFBBFFRLR`: `F`
+/// and `B` pick the front or back half of the remaining rows, then `L` and `R` pick the left
+/// or right half of the remaining columns. Read as binary, with `B` and `R` as 1, the row
+/// characters are the row and the column characters are the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoardingPass {
+    pub row: u32,
+    pub column: u32,
+    layout: PlaneLayout,
+}
 
-    all_ids.sort();
+impl BoardingPass {
+    pub fn new(row: u32, column: u32, layout: PlaneLayout) -> Result<Self, BoardingPassError> {
+        if row as u64 >= layout.rows() || column as u64 >= layout.columns() {
+            return Err(BoardingPassError::SeatOutOfRange { row, column });
+        }
 
-    // all_ids.iter().for_each(|i| println!("id: {:?}", i));
+        Ok(Self {
+            row,
+            column,
+            layout,
+        })
+    }
 
-    let mut prev = 0;
-    for ticket in all_ids.clone() {
-        if prev == 0 {
-            prev = ticket.id;
-            continue;
+    pub fn decode(code: &str, layout: PlaneLayout) -> Result<Self, BoardingPassError> {
+        let length = code.chars().count();
+        if length != layout.pass_length() {
+            return Err(BoardingPassError::WrongLength {
+                length,
+                expected: layout.pass_length(),
+            });
         }
 
-        if prev == (ticket.id - 1) {
-            prev = ticket.id;
-            continue;
+        let mut row = 0;
+        let mut column = 0;
+
+        for (i, c) in code.chars().enumerate() {
+            let is_row = i < layout.row_bits as usize;
+
+            let bit = match (is_row, c) {
+                (true, 'F') | (false, 'L') => 0,
+                (true, 'B') | (false, 'R') => 1,
+                _ => {
+                    return Err(BoardingPassError::InvalidCharacter {
+                        position: i + 1,
+                        character: c,
+                        expected: if is_row { "F or B" } else { "L or R" },
+                    })
+                }
+            };
+
+            if is_row {
+                row = (row << 1) | bit;
+            } else {
+                column = (column << 1) | bit;
+            }
         }
 
-        // this is it???
-        return ticket.id - 1;
+        Ok(Self {
+            row,
+            column,
+            layout,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let row_bits = self.layout.row_bits;
+        let column_bits = self.layout.column_bits;
+
+        let row = (0..row_bits)
+            .rev()
+            .map(|bit| if self.row >> bit & 1 == 1 { 'B' } else { 'F' });
+        let column = (0..column_bits).rev().map(|bit| {
+            if self.column >> bit & 1 == 1 {
+                'R'
+            } else {
+                'L'
+            }
+        });
+
+        row.chain(column).collect()
     }
 
-    panic!("this shouldnt be hit")
+    pub fn layout(&self) -> PlaneLayout {
+        self.layout
+    }
+
+    /// the row times the number of columns, plus the column
+    pub fn seat_id(&self) -> u32 {
+        ((self.row as u64) << self.layout.column_bits | self.column as u64) as u32
+    }
 }
 
-fn calculate_row(input: &str, row_count: usize) -> u32 {
-    assert_eq!(input.len(), 7);
+impl FromStr for BoardingPass {
+    type Err = BoardingPassError;
 
-    let mut possible_rows: Vec<usize> = (0..row_count).collect();
+    /// decodes a pass for the puzzle's plane
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode(s.trim(), PlaneLayout::DEFAULT)
+    }
+}
 
-    binary_search(input.chars(), &mut possible_rows)
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
 }
 
-fn calculate_column(input: &str, column_count: usize) -> u32 {
-    assert_eq!(input.len(), 3);
+/// A seat on a [`SeatingChart`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seat {
+    /// someone has a boarding pass for it
+    Taken,
+    Free,
+    /// before the first or after the last taken seat, so it is not on this aircraft
+    Missing,
+}
 
-    let mut possible_columns: Vec<usize> = (0..column_count).collect();
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Seat::Taken => "#",
+            Seat::Free => ".",
+            Seat::Missing => "-",
+        })
+    }
+}
 
-    binary_search(input.chars(), &mut possible_columns)
+/// Every seat on the plane, worked out from all the boarding passes. Some seats at the very
+/// front and back of the plane don't exist on this aircraft, so seats before the first taken
+/// seat or after the last one are [`Seat::Missing`] rather than free.
+#[derive(Debug, Clone)]
+pub struct SeatingChart {
+    layout: PlaneLayout,
+    seats: Grid<Seat>,
+    duplicates: Vec<BoardingPass>,
 }
 
-fn binary_search(mut chars: Chars, possible_seat: &mut [usize]) -> u32 {
-    let max = possible_seat.len();
-    let min = 0;
-    let mut mid = min + ((max - min) / 2);
-    mid = ((mid as f32).floor()) as usize;
+impl SeatingChart {
+    /// Panics if a pass is for a different layout, or the plane is too big to keep in memory
+    pub fn new(layout: PlaneLayout, passes: &[BoardingPass]) -> Self {
+        let rows = layout.rows() as usize;
+        let columns = layout.columns() as usize;
+        let mut seats = Grid::new(columns, rows, vec![Seat::Free; rows * columns]);
+        let mut duplicates = Vec::new();
+
+        for pass in passes {
+            assert_eq!(pass.layout(), layout, "{} is for a different plane", pass);
+
+            let seat = seats
+                .get_mut(pass.row as usize, pass.column as usize)
+                .unwrap();
+            if *seat == Seat::Taken {
+                duplicates.push(*pass);
+            }
+            *seat = Seat::Taken;
+        }
+
+        let taken_ids = || passes.iter().map(|pass| pass.seat_id() as usize);
+        let first = taken_ids().min().unwrap_or(rows * columns);
+        let last = taken_ids().max().unwrap_or(0);
+
+        for id in (0..first).chain((last + 1)..(rows * columns)) {
+            *seats.get_mut(id / columns, id % columns).unwrap() = Seat::Missing;
+        }
+
+        Self {
+            layout,
+            seats,
+            duplicates,
+        }
+    }
+
+    pub fn layout(&self) -> PlaneLayout {
+        self.layout
+    }
+
+    pub fn seat(&self, row: u32, column: u32) -> Option<Seat> {
+        self.seats.get(row as usize, column as usize).copied()
+    }
+
+    /// every free seat, front to back
+    pub fn free_seats(&self) -> Vec<BoardingPass> {
+        self.seats_where(|_, seat| seat == Seat::Free)
+    }
+
+    /// Every free seat where the seats with the ids either side of it are taken, front to back.
+    /// Our seat is one of these.
+    pub fn free_seats_between_taken(&self) -> Vec<BoardingPass> {
+        let columns = self.seats.width();
+        let is_taken = |id: usize| self.seats.get(id / columns, id % columns) == Some(&Seat::Taken);
+
+        self.seats_where(|id, seat| {
+            seat == Seat::Free && id > 0 && is_taken(id - 1) && is_taken(id + 1)
+        })
+    }
+
+    /// every pass for a seat that already had a pass, in the order they were given
+    pub fn duplicates(&self) -> &[BoardingPass] {
+        &self.duplicates
+    }
 
-    // println!("chars: {:?}", chars);
-    // println!("rows: {:?}", possible_seat);
-    // println!("min: {:?} mid: {:?} max: {:?}", min, mid, max);
+    fn seats_where<F>(&self, predicate: F) -> Vec<BoardingPass>
+    where
+        F: Fn(usize, Seat) -> bool,
+    {
+        self.seats
+            .iter()
+            .enumerate()
+            .filter(|(id, (_, seat))| predicate(*id, **seat))
+            .map(|(_, ((row, column), _))| BoardingPass {
+                row: row as u32,
+                column: column as u32,
+                layout: self.layout,
+            })
+            .collect()
+    }
+}
 
-    assert!(max > 0);
+impl fmt::Display for SeatingChart {
+    /// One line per row, front first, with the row number before it. Taken seats are `#`,
+    /// free seats `.` and seats that are not on the aircraft `-`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label_width = (self.seats.height().max(1) - 1).to_string().len();
+
+        for (row, seats) in self.seats.rows().enumerate() {
+            write!(f, "{:>width$} ", row, width = label_width)?;
+            for seat in seats {
+                write!(f, "{}", seat)?;
+            }
+            writeln!(f)?;
+        }
 
-    let c = chars.next();
-    if c.is_none() {
-        // println!("no more chars!");
-        assert_eq!(possible_seat.len(), 1);
-        return possible_seat[0] as u32;
+        Ok(())
     }
+}
+
+/// Why a boarding pass could not be made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardingPassError {
+    WrongLength {
+        length: usize,
+        expected: usize,
+    },
+    InvalidCharacter {
+        /// 1-based
+        position: usize,
+        character: char,
+        expected: &'static str,
+    },
+    /// the seat is not on the plane
+    SeatOutOfRange {
+        row: u32,
+        column: u32,
+    },
+}
 
-    match c.unwrap() {
-        'F' => binary_search(chars, &mut possible_seat[min..mid]),
-        'B' => binary_search(chars, &mut possible_seat[mid..max]),
-        'L' => binary_search(chars, &mut possible_seat[min..mid]),
-        'R' => binary_search(chars, &mut possible_seat[mid..max]),
-        unexpected => panic!("unexpected character: {:?}", unexpected),
+impl fmt::Display for BoardingPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardingPassError::WrongLength { length, expected } => write!(
+                f,
+                "boarding pass has {} characters, expected {}",
+                length, expected
+            ),
+            BoardingPassError::InvalidCharacter {
+                position,
+                character,
+                expected,
+            } => write!(
+                f,
+                "unexpected character {:?} at position {}, expected {}",
+                character, position, expected
+            ),
+            BoardingPassError::SeatOutOfRange { row, column } => {
+                write!(f, "row {}, column {} is not on the plane", row, column)
+            }
+        }
     }
 }
 
+impl Error for BoardingPassError {}
+
 #[cfg(test)]
 mod tests {
-    use super::{calculate_column, calculate_row};
+    use super::{BoardingPass, BoardingPassError, Day5, PlaneLayout, Seat, SeatingChart};
+    use crate::solution::{Answer, Solution};
+
+    fn passes(layout: PlaneLayout, seat_ids: &[u32]) -> Vec<BoardingPass> {
+        let columns = layout.columns() as u32;
+        seat_ids
+            .iter()
+            .map(|id| BoardingPass::new(id / columns, id % columns, layout).unwrap())
+            .collect()
+    }
 
     #[test]
-    fn calulate_row_happy() {
-        let ticket_data = "FBFBBFFRLR";
+    fn decode_examples() {
+        for (code, row, column, seat_id) in [
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ] {
+            let pass = code.parse::<BoardingPass>().unwrap();
+
+            assert_eq!(
+                (pass.row, pass.column, pass.seat_id()),
+                (row, column, seat_id)
+            );
+            assert_eq!(pass.encode(), code);
+        }
+    }
 
-        let result = calculate_row(&ticket_data[..7], 128);
+    #[test]
+    fn other_layouts() {
+        let layout = PlaneLayout::new(2, 1);
+
+        let pass = BoardingPass::decode("BFR", layout).unwrap();
+        assert_eq!((pass.row, pass.column, pass.seat_id()), (2, 1, 5));
+
+        let pass = BoardingPass::new(3, 0, layout).unwrap();
+        assert_eq!(pass.to_string(), "BBL");
+        assert_eq!(
+            BoardingPass::new(4, 0, layout),
+            Err(BoardingPassError::SeatOutOfRange { row: 4, column: 0 })
+        );
+    }
 
-        assert_eq!(result, 44);
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            "FBFBBFFRL".parse::<BoardingPass>(),
+            Err(BoardingPassError::WrongLength {
+                length: 9,
+                expected: 10
+            })
+        );
+        assert_eq!(
+            "FBFBBFFRLB".parse::<BoardingPass>(),
+            Err(BoardingPassError::InvalidCharacter {
+                position: 10,
+                character: 'B',
+                expected: "L or R"
+            })
+        );
+        assert_eq!(
+            "FBFBBFFRLB"
+                .parse::<BoardingPass>()
+                .unwrap_err()
+                .to_string(),
+            "unexpected character 'B' at position 10, expected L or R"
+        );
     }
 
     #[test]
-    fn calculate_column_happy() {
-        let ticket_data = "FBFBBFFRLR";
+    fn seating_chart() {
+        let layout = PlaneLayout::new(2, 2);
+        let chart = SeatingChart::new(layout, &passes(layout, &[3, 2, 4, 6, 7, 3, 8, 9]));
+
+        assert_eq!(
+            chart.to_string(),
+            "0 --##
+1 #.##
+2 ##--
+3 ----
+"
+        );
+        assert_eq!(chart.seat(1, 1), Some(Seat::Free));
+        assert_eq!(chart.seat(0, 0), Some(Seat::Missing));
+        assert_eq!(chart.seat(4, 0), None);
+
+        let free: Vec<u32> = chart.free_seats().iter().map(|s| s.seat_id()).collect();
+        assert_eq!(free, vec![5]);
+        let between: Vec<u32> = chart
+            .free_seats_between_taken()
+            .iter()
+            .map(|s| s.seat_id())
+            .collect();
+        assert_eq!(between, vec![5]);
+
+        let duplicates: Vec<u32> = chart.duplicates().iter().map(|s| s.seat_id()).collect();
+        assert_eq!(duplicates, vec![3]);
+    }
 
-        let column = calculate_column(&ticket_data[7..], 8);
+    #[test]
+    fn seat_zero_can_be_taken() {
+        let layout = PlaneLayout::new(1, 2);
+        let chart = SeatingChart::new(layout, &passes(layout, &[0, 2, 3, 5]));
+
+        let between: Vec<u32> = chart
+            .free_seats_between_taken()
+            .iter()
+            .map(|s| s.seat_id())
+            .collect();
+        assert_eq!(between, vec![1, 4]);
+        assert_eq!(chart.to_string(), "0 #.##\n1 .#--\n");
+    }
 
-        assert_eq!(column, 5);
+    #[test]
+    fn no_passes_has_no_answer() {
+        let passes = <Day5 as Solution>::parse(&[]).unwrap();
+
+        assert_eq!(
+            Day5::part1(&passes),
+            Answer::from("there are no boarding passes")
+        );
+        assert_eq!(
+            Day5::part2(&passes),
+            Answer::from("there is no free seat between two taken seats")
+        );
     }
 }
@@ -1,74 +1,260 @@
-use std::collections::HashSet;
+use std::{fmt, ops::BitOr};
 
-pub fn part1(input: &[String]) -> u32 {
-    let mut running_sum = 0;
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
 
-    let mut curr_group_lines = Vec::new();
-    for line in input {
-        if line.is_empty() {
-            running_sum += calculate_unique_yes_answers(&curr_group_lines);
-            curr_group_lines.clear();
-            continue;
-        }
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
 
-        curr_group_lines.push(line.clone());
+    type Input = Vec<GroupAnswers>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        parse_groups(input)
     }
 
-    // get last group too
-    running_sum += calculate_unique_yes_answers(&curr_group_lines);
+    fn part1(input: &Self::Input) -> Answer {
+        count_anyone(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        count_everyone(input).into()
+    }
+}
 
-    running_sum
+pub fn part1(input: &[String]) -> usize {
+    count_anyone(&parse_groups(input).unwrap())
 }
 
 pub fn part2(input: &[String]) -> usize {
-    input
-        .split(|line| line.is_empty())
-        .map(|group| calculate_everyone_yes_answers(group))
-        .sum()
+    count_everyone(&parse_groups(input).unwrap())
+}
+
+fn count_anyone(groups: &[GroupAnswers]) -> usize {
+    groups.iter().map(|group| group.union().len()).sum()
+}
+
+fn count_everyone(groups: &[GroupAnswers]) -> usize {
+    groups.iter().map(|group| group.intersection().len()).sum()
+}
+
+pub fn parse_groups(input: &[String]) -> Result<Vec<GroupAnswers>, LineError> {
+    input_utils::groups(input)
+        .map(|group| {
+            let people = group
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    line.trim()
+                        .parse()
+                        .map_err(|e: String| LineError::new(group.line_index + i, line, e))
+                })
+                .collect::<Result<_, _>>()?;
+
+            Ok(GroupAnswers::new(people))
+        })
+        .collect()
+}
+
+/// The number of questions on the customs declaration form, `a` to `z`
+pub const QUESTIONS: usize = 26;
+
+/// A set of questions, one bit per question with `a` as the lowest bit
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Questions(u32);
+
+impl Questions {
+    pub const NONE: Questions = Questions(0);
+    pub const ALL: Questions = Questions((1 << QUESTIONS) - 1);
+
+    pub fn contains(self, question: char) -> bool {
+        question_bit(question).is_some_and(|bit| self.0 & bit != 0)
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: Questions) -> Questions {
+        Questions(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Questions) -> Questions {
+        Questions(self.0 & other.0)
+    }
+
+    pub fn symmetric_difference(self, other: Questions) -> Questions {
+        Questions(self.0 ^ other.0)
+    }
+
+    /// the questions in the set, in alphabetical order
+    pub fn iter(self) -> impl Iterator<Item = char> {
+        ('a'..='z').filter(move |&question| self.contains(question))
+    }
+}
+
+fn question_bit(question: char) -> Option<u32> {
+    if question.is_ascii_lowercase() {
+        Some(1 << (question as u32 - 'a' as u32))
+    } else {
+        None
+    }
+}
+
+impl BitOr for Questions {
+    type Output = Questions;
+
+    fn bitor(self, other: Questions) -> Questions {
+        self.union(other)
+    }
 }
 
-fn calculate_unique_yes_answers(group_answers: &[String]) -> u32 {
-    let mut unique_yesses = HashSet::new();
+impl std::str::FromStr for Questions {
+    type Err = String;
 
-    group_answers.iter().for_each(|line| {
-        line.chars().for_each(|c| {
-            unique_yesses.insert(c);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars().try_fold(Questions::NONE, |questions, c| {
+            question_bit(c)
+                .map(|bit| Questions(questions.0 | bit))
+                .ok_or_else(|| format!("{:?} is not a question, expected a to z", c))
         })
-    });
+    }
+}
 
-    unique_yesses.len() as u32
+impl fmt::Display for Questions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|question| write!(f, "{}", question))
+    }
 }
 
-fn calculate_everyone_yes_answers(group_answers: &[String]) -> usize {
-    // get everything in the first list, and then check each other
-    // person to see if the answer is present
-    assert!(group_answers.len() > 0);
+/// The questions each person in a group answered "yes" to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupAnswers {
+    people: Vec<Questions>,
+}
 
-    let mut first = false;
+impl GroupAnswers {
+    pub fn new(people: Vec<Questions>) -> Self {
+        Self { people }
+    }
 
-    let result = group_answers
-        .iter()
-        .map::<HashSet<char>, _>(|line| line.chars().collect())
-        .fold(HashSet::new(), |acc: HashSet<char>, line| {
-            if !first && acc.is_empty() {
-                first = true;
-                return line;
-            }
+    pub fn people(&self) -> &[Questions] {
+        &self.people
+    }
+
+    /// the questions anyone in the group answered
+    pub fn union(&self) -> Questions {
+        self.people
+            .iter()
+            .fold(Questions::NONE, |acc, &person| acc.union(person))
+    }
+
+    /// the questions everyone in the group answered, which is none for an empty group
+    pub fn intersection(&self) -> Questions {
+        if self.people.is_empty() {
+            return Questions::NONE;
+        }
+
+        self.people
+            .iter()
+            .fold(Questions::ALL, |acc, &person| acc.intersection(person))
+    }
 
-            acc.intersection(&line).cloned().collect()
+    /// the questions answered by an odd number of people in the group
+    pub fn symmetric_difference(&self) -> Questions {
+        self.people.iter().fold(Questions::NONE, |acc, &person| {
+            acc.symmetric_difference(person)
         })
-        .len();
+    }
+
+    /// the questions answered by exactly `k` people in the group
+    pub fn answered_by_exactly(&self, k: usize) -> Questions {
+        self.counts()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count == k)
+            .fold(Questions::NONE, |acc, (i, _)| acc | Questions(1 << i))
+    }
+
+    /// how many people answered each question, indexed from `a`
+    pub fn counts(&self) -> [usize; QUESTIONS] {
+        let mut counts = [0; QUESTIONS];
+
+        for person in &self.people {
+            for (i, count) in counts.iter_mut().enumerate() {
+                if person.0 & (1 << i) != 0 {
+                    *count += 1;
+                }
+            }
+        }
+
+        counts
+    }
+}
+
+/// Per-question totals across every group, indexed from `a`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    /// how many people answered the question
+    pub people: [usize; QUESTIONS],
+    /// how many groups had at least one person answer the question
+    pub anyone: [usize; QUESTIONS],
+    /// how many groups had every person answer the question
+    pub everyone: [usize; QUESTIONS],
+}
 
-    result
+impl Histogram {
+    pub fn new(groups: &[GroupAnswers]) -> Self {
+        let mut histogram = Histogram {
+            people: [0; QUESTIONS],
+            anyone: [0; QUESTIONS],
+            everyone: [0; QUESTIONS],
+        };
+
+        for group in groups {
+            let (union, intersection) = (group.union(), group.intersection());
+
+            for (i, count) in group.counts().iter().enumerate() {
+                histogram.people[i] += count;
+                histogram.anyone[i] += (union.0 >> i & 1) as usize;
+                histogram.everyone[i] += (intersection.0 >> i & 1) as usize;
+            }
+        }
+
+        histogram
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "question  people  anyone  everyone")?;
+
+        for (i, question) in ('a'..='z').enumerate() {
+            writeln!(
+                f,
+                "{:>8}  {:>6}  {:>6}  {:>8}",
+                question, self.people[i], self.anyone[i], self.everyone[i]
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{parse_groups, part1, part2, GroupAnswers, Histogram, Questions};
 
-    #[test]
-    fn part_1_happy() {
-        let input = "abc
+    const EXAMPLE: &str = "abc
 
 a
 b
@@ -84,33 +270,68 @@ a
 
 b";
 
-        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(|line| line.to_string()).collect()
+    }
 
-        let result = part1(&lines);
+    fn group(people: &[&str]) -> GroupAnswers {
+        GroupAnswers::new(
+            people
+                .iter()
+                .map(|person| person.parse().unwrap())
+                .collect(),
+        )
+    }
 
-        assert_eq!(result, 11);
+    #[test]
+    fn part_1_happy() {
+        assert_eq!(part1(&lines(EXAMPLE)), 11);
     }
 
     #[test]
     fn part_2_happy() {
-        let input = "abc
+        assert_eq!(part2(&lines(EXAMPLE)), 6);
+    }
 
-a
-b
-c
+    #[test]
+    fn crlf_and_extra_blank_lines() {
+        let lines: Vec<String> = "abc\r\n\r\n\r\nab\r\nac\r\n"
+            .split('\n')
+            .map(|line| line.to_string())
+            .collect();
 
-ab
-ac
+        assert_eq!(part1(&lines), 6);
+        assert_eq!(part2(&lines), 4);
+    }
 
-a
-a
-a
-a
+    #[test]
+    fn set_queries() {
+        let group = group(&["abc", "abd", "ae"]);
 
-b";
-        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
-        let result = part2(&lines);
+        assert_eq!(group.union().to_string(), "abcde");
+        assert_eq!(group.intersection().to_string(), "a");
+        assert_eq!(group.symmetric_difference().to_string(), "acde");
+        assert_eq!(group.answered_by_exactly(2).to_string(), "b");
+        assert_eq!(group.answered_by_exactly(1).to_string(), "cde");
+        assert_eq!(group.answered_by_exactly(0).len(), 21);
+        assert!(GroupAnswers::new(vec![]).intersection().is_empty());
+        assert_eq!(Questions::ALL.len(), 26);
+    }
+
+    #[test]
+    fn histogram() {
+        let histogram = Histogram::new(&parse_groups(&lines(EXAMPLE)).unwrap());
+
+        assert_eq!(histogram.people[..3], [8, 4, 3]);
+        assert_eq!(histogram.anyone[..3], [4, 4, 3]);
+        assert_eq!(histogram.everyone[..3], [3, 2, 1]);
+        assert_eq!(histogram.people[3..], [0; 23]);
+    }
+
+    #[test]
+    fn rejects_unknown_answers() {
+        let error = parse_groups(&lines("ab\n\nAb")).unwrap_err();
 
-        assert_eq!(result, 6);
+        assert_eq!(error.line_number, 3);
     }
 }
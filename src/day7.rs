@@ -1,135 +1,611 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt, io,
+    str::FromStr,
+};
+
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+/// The colour both parts of the puzzle ask about
+pub const SHINY_GOLD: &str = "shiny gold";
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Input = BagGraph;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        let rules: Vec<BagRule> = input_utils::parse_all_as(input)?;
+
+        BagGraph::try_new(&rules).map_err(|errors| {
+            let line_index = errors[0].line_number() - 1;
+            LineError::new(line_index, &input[line_index], RuleErrors(errors))
+        })
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        input.ancestors(SHINY_GOLD).len().into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        input.contained_count(SHINY_GOLD).into()
+    }
+}
 
 pub fn part1(input: &[String]) -> usize {
-    let rules = input.iter().map(|line| parse_rule(line)).fold(
-        HashMap::new(),
-        |mut acc, (inner_bag, map)| {
-            acc.insert(inner_bag, map);
-            acc
-        },
-    );
-
-    let mut cache = HashMap::<String, bool>::new();
-
-    fn contains_shiny_gold(
-        rules: &HashMap<&str, HashMap<&str, u32>>,
-        contains: &mut HashMap<String, bool>,
-        bag_colour: &str,
-    ) -> bool {
-        return contains.get(bag_colour).copied().unwrap_or_else(|| {
-            for (inner_colour, _) in rules.get(bag_colour).unwrap().iter() {
-                if *inner_colour == "shiny gold bag"
-                    || contains_shiny_gold(rules, contains, inner_colour)
-                {
-                    contains.insert(String::from(bag_colour), true);
-                    return true;
-                }
+    parse_graph(input).ancestors(SHINY_GOLD).len()
+}
+
+pub fn part2(input: &[String]) -> u64 {
+    parse_graph(input).contained_count(SHINY_GOLD)
+}
+
+fn parse_graph(input: &[String]) -> BagGraph {
+    BagGraph::try_new(&input_utils::parse_all_as::<BagRule>(input).unwrap()).unwrap()
+}
+
+/// One line of the puzzle input: the bags that must be directly inside a bag of some colour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagRule {
+    pub colour: String,
+    /// `(colour, quantity)` in the order the rule lists them
+    pub contents: Vec<(String, u32)>,
+}
+
+impl FromStr for BagRule {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (colour, contents) = s.trim().split_once(" bags contain ").ok_or_else(|| {
+            invalid_input(format!(
+                "{:?} does not look like \"<colour> bags contain ...\"",
+                s
+            ))
+        })?;
+        let contents = contents
+            .strip_suffix('.')
+            .ok_or_else(|| invalid_input(format!("{:?} does not end with a full stop", s)))?;
+
+        if contents == "no other bags" {
+            return Ok(BagRule {
+                colour: colour.to_string(),
+                contents: vec![],
+            });
+        }
+
+        let contents = contents
+            .split(", ")
+            .map(|item| {
+                let bad_item = || {
+                    invalid_input(format!(
+                        "{:?} does not look like \"<quantity> <colour> bags\"",
+                        item
+                    ))
+                };
+
+                let (quantity, rest) = item.split_once(' ').ok_or_else(bad_item)?;
+                let quantity = quantity.parse::<u32>().map_err(|_| bad_item())?;
+                let inner_colour = rest
+                    .strip_suffix(" bags")
+                    .or_else(|| rest.strip_suffix(" bag"))
+                    .ok_or_else(bad_item)?;
+
+                Ok((inner_colour.to_string(), quantity))
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        Ok(BagRule {
+            colour: colour.to_string(),
+            contents,
+        })
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// A problem with a set of bag rules. Most would make queries over the rules wrong or never
+/// finish; see [`RuleError::is_fatal`]. Line numbers are 1-based and assume one rule per line,
+/// as in the puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// the colour already has a rule on an earlier line
+    Duplicate {
+        colour: String,
+        line_number: usize,
+        first_line_number: usize,
+    },
+    /// a rule says a bag holds this colour, but no rule defines it
+    Undefined { colour: String, line_number: usize },
+    /// each colour in the chain holds the next, and the last is the same as the first, so a
+    /// bag would have to hold itself
+    Cycle {
+        chain: Vec<String>,
+        line_number: usize,
+    },
+    /// the colour holds no other bags and no rule mentions it, so it is never part of any
+    /// containment. This is only a warning.
+    Unreachable { colour: String, line_number: usize },
+}
+
+impl RuleError {
+    /// whether the rules cannot be loaded because of this problem, rather than it just being
+    /// worth a warning
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, RuleError::Unreachable { .. })
+    }
+
+    /// the line of the rule the problem was found in
+    pub fn line_number(&self) -> usize {
+        match self {
+            RuleError::Duplicate { line_number, .. }
+            | RuleError::Undefined { line_number, .. }
+            | RuleError::Cycle { line_number, .. }
+            | RuleError::Unreachable { line_number, .. } => *line_number,
+        }
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Duplicate {
+                colour,
+                first_line_number,
+                ..
+            } => write!(
+                f,
+                "{} bags are already defined on line {}",
+                colour, first_line_number
+            ),
+            RuleError::Undefined { colour, .. } => {
+                write!(f, "{} bags are never defined", colour)
             }
-            contains.insert(String::from(bag_colour), false);
-            false
-        });
+            RuleError::Cycle { chain, .. } => {
+                write!(f, "bags hold themselves: {}", chain.join(" -> "))
+            }
+            RuleError::Unreachable { colour, .. } => write!(
+                f,
+                "{} bags hold no other bags and no rule mentions them",
+                colour
+            ),
+        }
     }
+}
 
-    let mut count = 0;
-    rules.iter().for_each(|(k, _v)| {
-        if contains_shiny_gold(&rules, &mut cache, k) {
-            count += 1;
+impl Error for RuleError {}
+
+/// Every fatal problem found when loading a set of rules, in the order [`validate`] found them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleErrors(pub Vec<RuleError>);
+
+impl fmt::Display for RuleErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.len() > 1 {
+            write!(f, "{} problems with the bag rules: ", self.0.len())?;
+        }
+
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; line {}: ", error.line_number())?;
+            }
+            write!(f, "{}", error)?;
         }
-    });
 
-    count
+        Ok(())
+    }
 }
 
-pub fn part2(input: &[String]) -> u32 {
-    let rules = input.iter().map(|line| parse_rule(line)).fold(
-        HashMap::new(),
-        |mut acc, (inner_bag, map)| {
-            acc.insert(inner_bag, map);
-            acc
-        },
-    );
+impl Error for RuleErrors {}
 
-    let mut cache = HashMap::<String, usize>::new();
+/// Checks a set of rules for duplicate definitions, undefined colours, cycles and unreachable
+/// colours, in that order, with each kind in line order. Unreachable colours are only warnings,
+/// so check [`RuleError::is_fatal`] before refusing the rules.
+pub fn validate(rules: &[BagRule]) -> Vec<RuleError> {
+    let mut errors = vec![];
+    let mut definitions: HashMap<&str, usize> = HashMap::new();
 
-    fn get_inner_bag_count(
-        rules: &HashMap<&str, HashMap<&str, u32>>,
-        cache: &mut HashMap<String, usize>,
-        bag_colour: &str,
-    ) -> usize {
-        return cache.get(bag_colour).copied().unwrap_or_else(|| {
-            let mut count = 0;
-            for (inner_colour, quantity) in rules.get(bag_colour).unwrap().iter() {
-                count +=
-                    (1 + get_inner_bag_count(rules, cache, inner_colour)) * (*quantity as usize);
+    for (i, rule) in rules.iter().enumerate() {
+        if let Some(&first) = definitions.get(rule.colour.as_str()) {
+            errors.push(RuleError::Duplicate {
+                colour: rule.colour.clone(),
+                line_number: i + 1,
+                first_line_number: first + 1,
+            });
+        } else {
+            definitions.insert(&rule.colour, i);
+        }
+    }
+
+    for (i, rule) in rules.iter().enumerate() {
+        for (colour, _) in &rule.contents {
+            if !definitions.contains_key(colour.as_str()) {
+                errors.push(RuleError::Undefined {
+                    colour: colour.clone(),
+                    line_number: i + 1,
+                });
             }
-            cache.insert(String::from(bag_colour), count);
-            count
+        }
+    }
+
+    let graph = BagGraph::unchecked(rules);
+    for chain in graph.cycles() {
+        let line_number = definitions[chain[0]] + 1;
+        errors.push(RuleError::Cycle {
+            chain: chain.into_iter().map(String::from).collect(),
+            line_number,
         });
     }
 
-    get_inner_bag_count(&rules, &mut cache, "shiny gold bag") as u32
+    for (i, rule) in rules.iter().enumerate() {
+        if rule.contents.is_empty() && graph.parents(&rule.colour).is_empty() {
+            errors.push(RuleError::Unreachable {
+                colour: rule.colour.clone(),
+                line_number: i + 1,
+            });
+        }
+    }
+
+    errors
+}
+
+/// Every bag rule as a directed graph, with an edge from each bag to the bags it must
+/// directly contain. Queries take colours without the trailing "bags", e.g. `"shiny gold"`.
+#[derive(Debug, Clone)]
+pub struct BagGraph {
+    colours: Vec<String>,
+    indices: HashMap<String, usize>,
+    /// `(colour index, quantity)` of the bags directly inside each colour
+    children: Vec<Vec<(usize, u32)>>,
+    /// `(colour index, quantity)` of the bags each colour is directly inside
+    parents: Vec<Vec<(usize, u32)>>,
 }
 
-fn parse_rule(rule_line: &str) -> (&str, HashMap<&str, u32>) {
-    let rule_split: Vec<&str> = rule_line.split("contain").collect();
-    assert_eq!(rule_split.len(), 2);
+impl BagGraph {
+    /// Builds the graph, failing with every fatal problem [`validate`] finds so that no query
+    /// can run on rules with a cycle. Warnings are ignored.
+    pub fn try_new(rules: &[BagRule]) -> Result<Self, Vec<RuleError>> {
+        let errors: Vec<RuleError> = validate(rules)
+            .into_iter()
+            .filter(RuleError::is_fatal)
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Self::unchecked(rules))
+    }
 
-    let outer_bag = rule_split[0].trim().trim_end_matches("s");
-    let inner_bag_rules = rule_split[1].trim();
+    /// Builds the graph without checking the rules, so queries on it may never finish.
+    /// Colours that are referenced but never defined are treated as holding no other bags. If
+    /// a colour is defined more than once, its first rule wins.
+    fn unchecked(rules: &[BagRule]) -> Self {
+        let mut graph = BagGraph {
+            colours: vec![],
+            indices: HashMap::new(),
+            children: vec![],
+            parents: vec![],
+        };
+        let mut defined = HashSet::new();
+
+        for rule in rules {
+            let outer = graph.add_colour(&rule.colour);
+            if !defined.insert(outer) {
+                continue;
+            }
+
+            let contents = rule
+                .contents
+                .iter()
+                .map(|(colour, quantity)| (graph.add_colour(colour), *quantity))
+                .collect();
+            graph.children[outer] = contents;
+        }
+
+        for outer in 0..graph.colours.len() {
+            for &(inner, quantity) in &graph.children[outer] {
+                graph.parents[inner].push((outer, quantity));
+            }
+        }
+
+        graph
+    }
+
+    fn add_colour(&mut self, colour: &str) -> usize {
+        if let Some(&index) = self.indices.get(colour) {
+            return index;
+        }
+
+        let index = self.colours.len();
+        self.colours.push(colour.to_string());
+        self.indices.insert(colour.to_string(), index);
+        self.children.push(vec![]);
+        self.parents.push(vec![]);
+        index
+    }
 
-    let inner_bags: HashMap<&str, u32> = inner_bag_rules
-        .split_terminator::<_>(",")
-        .map(|rule| {
-            let rule_details: Vec<&str> =
-                rule.trim_end_matches(".").trim().splitn(2, ' ').collect();
-            // println!("Rule: {:?}", rule);
-            // println!("split result: {:?}", rule_details);
-            (rule_details[1].trim_end_matches("s"), rule_details[0])
+    /// every colour in the graph, in the order they first appear in the rules
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.colours.iter().map(String::as_str)
+    }
+
+    pub fn contains(&self, colour: &str) -> bool {
+        self.indices.contains_key(colour)
+    }
+
+    /// the bags that must be directly inside a bag of this colour, with how many of each
+    pub fn children(&self, colour: &str) -> Vec<(&str, u32)> {
+        self.named_edges(colour, &self.children)
+    }
+
+    /// the bags that can directly hold a bag of this colour, with how many they hold
+    pub fn parents(&self, colour: &str) -> Vec<(&str, u32)> {
+        self.named_edges(colour, &self.parents)
+    }
+
+    fn named_edges<'a>(
+        &'a self,
+        colour: &str,
+        edges: &'a [Vec<(usize, u32)>],
+    ) -> Vec<(&'a str, u32)> {
+        self.indices.get(colour).map_or_else(Vec::new, |&index| {
+            edges[index]
+                .iter()
+                .map(|&(other, quantity)| (self.colours[other].as_str(), quantity))
+                .collect()
         })
-        .fold(HashMap::new(), |mut acc, (k, v)| {
-            match v.parse::<u32>() {
-                Ok(num_bags) => {
-                    acc.insert(k, num_bags);
-                    ()
+    }
+
+    /// every colour that can eventually hold a bag of this colour, sorted by name
+    pub fn ancestors(&self, colour: &str) -> Vec<&str> {
+        self.reachable(colour, &self.parents)
+    }
+
+    /// every colour that must eventually be inside a bag of this colour, sorted by name
+    pub fn descendants(&self, colour: &str) -> Vec<&str> {
+        self.reachable(colour, &self.children)
+    }
+
+    fn reachable<'a>(&'a self, colour: &str, edges: &[Vec<(usize, u32)>]) -> Vec<&'a str> {
+        let start = match self.indices.get(colour) {
+            Some(&index) => index,
+            None => return vec![],
+        };
+
+        let mut seen = vec![false; self.colours.len()];
+        let mut stack = vec![start];
+
+        while let Some(index) = stack.pop() {
+            for &(next, _) in &edges[index] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
                 }
-                _ => (), // no parse because it is a "no other bags"
             }
-            acc
-        });
+        }
 
-    (outer_bag, inner_bags)
-}
+        let mut colours: Vec<&str> = seen
+            .iter()
+            .enumerate()
+            .filter(|&(index, &seen)| seen && index != start)
+            .map(|(index, _)| self.colours[index].as_str())
+            .collect();
+        colours.sort_unstable();
+        colours
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{parse_rule, part2};
+    /// How many bags in total must be inside a bag of this colour, not counting the bag
+    /// itself. This always finishes, because [`BagGraph::try_new`] refuses rules with a cycle.
+    pub fn contained_count(&self, colour: &str) -> u64 {
+        match self.indices.get(colour) {
+            Some(&index) => self.count_inside(index, &mut vec![None; self.colours.len()]),
+            None => 0,
+        }
+    }
 
-    #[test]
-    fn parse_rule_happy() {
-        let line = "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.";
+    fn count_inside(&self, index: usize, cache: &mut Vec<Option<u64>>) -> u64 {
+        if let Some(count) = cache[index] {
+            return count;
+        }
+
+        let count = self.children[index]
+            .iter()
+            .map(|&(inner, quantity)| u64::from(quantity) * (1 + self.count_inside(inner, cache)))
+            .sum();
+        cache[index] = Some(count);
+        count
+    }
+
+    /// Every chain of bags from `outer` down to `inner`, each starting with `outer` and ending
+    /// with `inner`. A chain never visits the same colour twice.
+    pub fn paths(&self, outer: &str, inner: &str) -> Vec<Vec<&str>> {
+        let (outer, inner) = match (self.indices.get(outer), self.indices.get(inner)) {
+            (Some(&outer), Some(&inner)) => (outer, inner),
+            _ => return vec![],
+        };
 
-        let (result_outer_bag, result_map) = parse_rule(line);
+        let mut paths = vec![];
+        let mut path = vec![outer];
+        self.extend_paths(inner, &mut path, &mut paths);
 
-        assert_eq!(result_outer_bag, "muted yellow bag");
-        assert!(result_map.contains_key("shiny gold bag"));
-        let gold_bag_count = result_map.get("shiny gold bag").unwrap().to_owned();
-        assert_eq!(gold_bag_count, 2);
-        let faded_blue_bag_count = result_map.get("faded blue bag").unwrap().to_owned();
-        assert_eq!(faded_blue_bag_count, 9);
+        paths
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|index| self.colours[index].as_str())
+                    .collect()
+            })
+            .collect()
     }
 
-    #[test]
-    fn parse_rule_no_inner_bag() {
-        let line = "dotted black bags contain no other bags.";
-        let (result_outer_bag, result_map) = parse_rule(line);
+    /// The graph in Graphviz DOT format, with an edge from each bag to the bags it holds,
+    /// labelled with how many. If `highlight` is given, that colour, the bags that can
+    /// eventually hold it and the bags it must eventually hold are filled in, along with the
+    /// edges between them.
+    pub fn to_dot(&self, highlight: Option<&str>) -> String {
+        let mut roles = vec![None; self.colours.len()];
+        if let Some(&index) = highlight.and_then(|colour| self.indices.get(colour)) {
+            for colour in self.ancestors(&self.colours[index]) {
+                roles[self.indices[colour]] = Some(Highlight::Ancestor);
+            }
+            for colour in self.descendants(&self.colours[index]) {
+                roles[self.indices[colour]] = Some(Highlight::Descendant);
+            }
+            roles[index] = Some(Highlight::Chosen);
+        }
+
+        let mut dot = String::from("digraph bags {\n    node [shape=box];\n");
+
+        for (index, colour) in self.colours.iter().enumerate() {
+            match roles[index] {
+                Some(role) => {
+                    dot += &format!(
+                        "    {} [style=filled, fillcolor={}];\n",
+                        dot_id(colour),
+                        role.fill_colour()
+                    )
+                }
+                None => dot += &format!("    {};\n", dot_id(colour)),
+            }
+        }
 
-        assert_eq!(result_outer_bag, "dotted black bag");
-        assert!(result_map.is_empty());
+        for (outer, contents) in self.children.iter().enumerate() {
+            for &(inner, quantity) in contents {
+                // an edge is on a highlighted chain if it leads into the highlighted colour
+                // from an ancestor, or out of it towards a descendant
+                let on_chain = matches!(
+                    (roles[outer], roles[inner]),
+                    (Some(Highlight::Ancestor), Some(Highlight::Ancestor))
+                        | (Some(Highlight::Ancestor), Some(Highlight::Chosen))
+                        | (Some(Highlight::Chosen), Some(Highlight::Descendant))
+                        | (Some(Highlight::Descendant), Some(Highlight::Descendant))
+                );
+
+                dot += &format!(
+                    "    {} -> {} [label=\"{}\"{}];\n",
+                    dot_id(&self.colours[outer]),
+                    dot_id(&self.colours[inner]),
+                    quantity,
+                    if on_chain { ", penwidth=2" } else { "" }
+                );
+            }
+        }
+
+        dot += "}\n";
+        dot
     }
 
-    #[test]
-    fn part2_happy1() {
-        let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+    /// Each cycle found by a depth-first search, as a chain of colours that starts and ends
+    /// with the same colour
+    fn cycles(&self) -> Vec<Vec<&str>> {
+        let mut state = vec![Visit::New; self.colours.len()];
+        let mut cycles = vec![];
+
+        for start in 0..self.colours.len() {
+            if state[start] == Visit::New {
+                self.find_cycles(start, &mut state, &mut vec![], &mut cycles);
+            }
+        }
+
+        cycles
+            .into_iter()
+            .map(|cycle| {
+                cycle
+                    .into_iter()
+                    .map(|index| self.colours[index].as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn find_cycles(
+        &self,
+        index: usize,
+        state: &mut Vec<Visit>,
+        path: &mut Vec<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        state[index] = Visit::OnPath;
+        path.push(index);
+
+        for &(next, _) in &self.children[index] {
+            match state[next] {
+                Visit::New => self.find_cycles(next, state, path, cycles),
+                Visit::OnPath => {
+                    let start = path.iter().position(|&i| i == next).unwrap();
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(next);
+                    cycles.push(cycle);
+                }
+                Visit::Done => {}
+            }
+        }
+
+        path.pop();
+        state[index] = Visit::Done;
+    }
+
+    fn extend_paths(&self, target: usize, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+        let last = *path.last().unwrap();
+        if last == target {
+            paths.push(path.clone());
+            return;
+        }
+
+        for &(next, _) in &self.children[last] {
+            if !path.contains(&next) {
+                path.push(next);
+                self.extend_paths(target, path, paths);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// How a bag is related to the colour [`BagGraph::to_dot`] was asked to highlight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Highlight {
+    Chosen,
+    Ancestor,
+    Descendant,
+}
+
+impl Highlight {
+    /// a Graphviz colour name
+    fn fill_colour(self) -> &'static str {
+        match self {
+            Highlight::Chosen => "gold",
+            Highlight::Ancestor => "lightblue",
+            Highlight::Descendant => "lightsalmon",
+        }
+    }
+}
+
+/// a quoted DOT identifier
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    OnPath,
+    Done,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{part1, part2, validate, BagGraph, BagRule, Day7, RuleError};
+    use crate::solution::Solution;
+
+    const EXAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
 muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
@@ -139,12 +615,58 @@ vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
 
-        // 1 + (1 * ((3 * 1) + (4 * 1))) + 2 + (2 * ((5 * 1) + (6 *1)))
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(|line| line.to_string()).collect()
+    }
+
+    fn graph(input: &str) -> BagGraph {
+        let rules: Vec<BagRule> = input.lines().map(|line| line.parse().unwrap()).collect();
+        BagGraph::try_new(&rules).unwrap()
+    }
+
+    #[test]
+    fn parse_rule_happy() {
+        let rule: BagRule = "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags."
+            .parse()
+            .unwrap();
 
-        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
-        let result = part2(&lines);
+        assert_eq!(rule.colour, "muted yellow");
+        assert_eq!(
+            rule.contents,
+            vec![("shiny gold".to_string(), 2), ("faded blue".to_string(), 9)]
+        );
+    }
+
+    #[test]
+    fn parse_rule_no_inner_bag() {
+        let rule: BagRule = "dotted black bags contain no other bags.".parse().unwrap();
 
-        assert_eq!(result, 32);
+        assert_eq!(rule.colour, "dotted black");
+        assert!(rule.contents.is_empty());
+    }
+
+    #[test]
+    fn parse_rule_errors() {
+        assert!("dotted black bags hold no other bags."
+            .parse::<BagRule>()
+            .is_err());
+        assert!("dotted black bags contain no other bags"
+            .parse::<BagRule>()
+            .is_err());
+        assert!("light red bags contain one bright white bag."
+            .parse::<BagRule>()
+            .is_err());
+    }
+
+    #[test]
+    fn part1_happy() {
+        assert_eq!(part1(&lines(EXAMPLE)), 4);
+    }
+
+    #[test]
+    fn part2_happy1() {
+        // 1 + (1 * ((3 * 1) + (4 * 1))) + 2 + (2 * ((5 * 1) + (6 *1)))
+        assert_eq!(part2(&lines(EXAMPLE)), 32);
     }
 
     #[test]
@@ -157,9 +679,177 @@ dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
 
-        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
-        let result = part2(&lines);
+        assert_eq!(part2(&lines(input)), 126);
+    }
+
+    #[test]
+    fn queries() {
+        let graph = graph(EXAMPLE);
+
+        assert_eq!(
+            graph.ancestors("shiny gold"),
+            vec!["bright white", "dark orange", "light red", "muted yellow"]
+        );
+        assert_eq!(
+            graph.descendants("shiny gold"),
+            vec!["dark olive", "dotted black", "faded blue", "vibrant plum"]
+        );
+        assert_eq!(
+            graph.parents("faded blue"),
+            vec![("muted yellow", 9), ("dark olive", 3), ("vibrant plum", 5)]
+        );
+        assert_eq!(graph.children("faded blue"), vec![]);
+        assert_eq!(graph.contained_count("dark olive"), 7);
+        assert!(graph.ancestors("no such").is_empty());
+        assert!(!graph.contains("no such"));
+    }
 
-        assert_eq!(result, 126);
+    #[test]
+    fn paths() {
+        let graph = graph(EXAMPLE);
+
+        assert_eq!(
+            graph.paths("light red", "shiny gold"),
+            vec![
+                vec!["light red", "bright white", "shiny gold"],
+                vec!["light red", "muted yellow", "shiny gold"],
+            ]
+        );
+        assert_eq!(
+            graph.paths("shiny gold", "light red"),
+            Vec::<Vec<&str>>::new()
+        );
+        assert_eq!(graph.paths("muted yellow", "faded blue").len(), 3);
+    }
+
+    #[test]
+    fn validation() {
+        let rules: Vec<BagRule> = "light red bags contain 1 bright white bag.
+bright white bags contain 2 muted yellow bags, 1 faded blue bag.
+muted yellow bags contain 3 light red bags.
+light red bags contain no other bags.
+dotted black bags contain no other bags."
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+
+        assert_eq!(
+            validate(&rules),
+            vec![
+                RuleError::Duplicate {
+                    colour: "light red".to_string(),
+                    line_number: 4,
+                    first_line_number: 1,
+                },
+                RuleError::Undefined {
+                    colour: "faded blue".to_string(),
+                    line_number: 2,
+                },
+                RuleError::Cycle {
+                    chain: vec![
+                        "light red".to_string(),
+                        "bright white".to_string(),
+                        "muted yellow".to_string(),
+                        "light red".to_string(),
+                    ],
+                    line_number: 1,
+                },
+                RuleError::Unreachable {
+                    colour: "dotted black".to_string(),
+                    line_number: 5,
+                },
+            ]
+        );
+        assert!(validate(&[]).is_empty());
+        assert!(!validate(&rules)[3].is_fatal());
+        assert_eq!(BagGraph::try_new(&rules).unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn unreachable_colours_still_load() {
+        let lines = lines(&format!(
+            "{}\nplaid teal bags contain no other bags.",
+            EXAMPLE
+        ));
+
+        assert!(<Day7 as Solution>::parse(&lines).is_ok());
+        assert_eq!(part1(&lines), 4);
+    }
+
+    #[test]
+    fn every_fatal_error_is_reported() {
+        let lines = lines(
+            "shiny gold bags contain 1 dark red bag.
+dark red bags contain 2 shiny gold bags, 1 faded blue bag.
+plaid teal bags contain no other bags.",
+        );
+
+        let error = <Day7 as Solution>::parse(&lines).unwrap_err();
+        assert_eq!(error.line_number, 2);
+        assert_eq!(
+            error.source.to_string(),
+            "2 problems with the bag rules: faded blue bags are never defined; \
+             line 1: bags hold themselves: shiny gold -> dark red -> shiny gold"
+        );
+    }
+
+    #[test]
+    fn invalid_rules_fail_to_load() {
+        let lines = lines(
+            "shiny gold bags contain 1 dark red bag.
+dark red bags contain 2 shiny gold bags.",
+        );
+
+        let error = <Day7 as Solution>::parse(&lines).unwrap_err();
+        assert_eq!(error.line_number, 1);
+        assert_eq!(
+            error.source.to_string(),
+            "bags hold themselves: shiny gold -> dark red -> shiny gold"
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let graph = graph(
+            "light red bags contain 1 bright white bag.
+bright white bags contain 2 shiny gold bags.
+shiny gold bags contain 3 faded blue bags.
+faded blue bags contain no other bags.
+dotted black bags contain 4 faded blue bags.",
+        );
+
+        assert_eq!(
+            graph.to_dot(None),
+            r#"digraph bags {
+    node [shape=box];
+    "light red";
+    "bright white";
+    "shiny gold";
+    "faded blue";
+    "dotted black";
+    "light red" -> "bright white" [label="1"];
+    "bright white" -> "shiny gold" [label="2"];
+    "shiny gold" -> "faded blue" [label="3"];
+    "dotted black" -> "faded blue" [label="4"];
+}
+"#
+        );
+
+        assert_eq!(
+            graph.to_dot(Some("bright white")),
+            r#"digraph bags {
+    node [shape=box];
+    "light red" [style=filled, fillcolor=lightblue];
+    "bright white" [style=filled, fillcolor=gold];
+    "shiny gold" [style=filled, fillcolor=lightsalmon];
+    "faded blue" [style=filled, fillcolor=lightsalmon];
+    "dotted black";
+    "light red" -> "bright white" [label="1", penwidth=2];
+    "bright white" -> "shiny gold" [label="2", penwidth=2];
+    "shiny gold" -> "faded blue" [label="3", penwidth=2];
+    "dotted black" -> "faded blue" [label="4"];
+}
+"#
+        );
     }
 }
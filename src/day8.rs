@@ -1,29 +1,58 @@
-use std::{cell::Cell, collections::HashSet, io::Error, str::FromStr};
+use std::{cell::Cell, collections::HashSet, io, str::FromStr};
+
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        accumulator_at_loop(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        match repaired_accumulator(input) {
+            Some(accumulator) => accumulator.into(),
+            None => Answer::from("no single swapped instruction lets the program finish"),
+        }
+    }
+}
 
 pub fn part1(input: &[String]) -> isize {
-    let instructions: Vec<Instruction> = input
-        .iter()
-        .map(|line| Instruction::from_str(line).unwrap())
-        .collect();
+    accumulator_at_loop(&input_utils::parse_all_as(input).unwrap())
+}
 
-    let cpu = CPU::new(instructions);
+pub fn part2(input: &[String]) -> Option<isize> {
+    repaired_accumulator(&input_utils::parse_all_as(input).unwrap())
+}
+
+/// the accumulator just before any instruction runs a second time, or when the program ends
+fn accumulator_at_loop(instructions: &[Instruction]) -> isize {
+    let cpu = Cpu::new(instructions.to_vec());
     cpu.run();
 
     cpu.get_accumulator_value()
 }
 
-pub fn part2(input: &[String]) -> isize {
-    let og_instructions: Vec<Instruction> = input
-        .iter()
-        .map(|line| Instruction::from_str(line).unwrap())
-        .collect();
-
+/// the accumulator after the program finishes with one `jmp` swapped for a `nop` or the other
+/// way round, if any single swap makes it finish
+fn repaired_accumulator(og_instructions: &[Instruction]) -> Option<isize> {
     let instruction_count = og_instructions.len();
 
     let mut next_start_index = 0;
 
     loop {
-        let mut changed_instructions = og_instructions.clone();
+        let mut changed_instructions = og_instructions.to_vec();
 
         let mut new_instruction: Option<Instruction> = None;
         let mut curr_swap_index = next_start_index;
@@ -33,13 +62,13 @@ pub fn part2(input: &[String]) -> isize {
             let curr_instruction = changed_instructions.get(index).unwrap();
 
             match *curr_instruction {
-                Instruction::JMP(v) => {
-                    new_instruction = Some(Instruction::NOP(v));
+                Instruction::Jmp(v) => {
+                    new_instruction = Some(Instruction::Nop(v));
                     curr_swap_index = index;
                     break;
                 }
-                Instruction::NOP(v) => {
-                    new_instruction = Some(Instruction::JMP(v));
+                Instruction::Nop(v) => {
+                    new_instruction = Some(Instruction::Jmp(v));
                     curr_swap_index = index;
                     break;
                 }
@@ -47,19 +76,20 @@ pub fn part2(input: &[String]) -> isize {
             }
         }
 
-        assert!(new_instruction.is_some());
+        // every jmp and nop has been tried
+        let new_instruction = new_instruction?;
 
         // swap the instruction!
-        changed_instructions[curr_swap_index] = new_instruction.unwrap();
+        changed_instructions[curr_swap_index] = new_instruction;
         next_start_index = curr_swap_index + 1;
 
         // now instantiate a CPU with the new instructions and lets see if it finishes :x
-        let cpu = CPU::new(changed_instructions);
+        let cpu = Cpu::new(changed_instructions);
         match cpu.run() {
             true => {
                 // this means the cpu instructions completed! return the result
                 let result = cpu.get_accumulator_value();
-                return result;
+                return Some(result);
             }
             false => {
                 // no result :(
@@ -74,13 +104,13 @@ pub fn part2(input: &[String]) -> isize {
     }
 }
 
-struct CPU {
+struct Cpu {
     accumulator: Cell<isize>,
     last_instruction_index: Cell<usize>,
     instructions: Vec<Instruction>,
 }
 
-impl CPU {
+impl Cpu {
     fn new(instructions: Vec<Instruction>) -> Self {
         Self {
             accumulator: Cell::new(0),
@@ -93,14 +123,12 @@ impl CPU {
         self.accumulator.get()
     }
 
+    #[allow(dead_code)]
     fn get_last_instruction(&self) -> (usize, Instruction) {
         let last_instruction_index = self.last_instruction_index.get();
         (
             last_instruction_index,
-            self.instructions
-                .get(last_instruction_index)
-                .unwrap()
-                .clone(),
+            *self.instructions.get(last_instruction_index).unwrap(),
         )
     }
 
@@ -116,7 +144,7 @@ impl CPU {
             }
 
             // a loop was found, meaning we terminated early
-            if instruction_cache.get(&(instruction_num)).is_some() {
+            if instruction_cache.contains(&instruction_num) {
                 return false;
             }
 
@@ -128,13 +156,13 @@ impl CPU {
             // println!("running instruction: {:?}", instruction);
 
             match instruction {
-                Instruction::NOP(_) => {}
-                Instruction::ACC(value) => {
+                Instruction::Nop(_) => {}
+                Instruction::Acc(value) => {
                     let prev = self.accumulator.get();
                     let result = prev.checked_add(*value).unwrap();
                     self.accumulator.set(result);
                 }
-                Instruction::JMP(jmp) => instruction_increment = *jmp,
+                Instruction::Jmp(jmp) => instruction_increment = *jmp,
             };
 
             instruction_cache.insert(instruction_num);
@@ -146,28 +174,40 @@ impl CPU {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Instruction {
-    NOP(isize),
-    ACC(isize),
-    JMP(isize),
+pub enum Instruction {
+    Nop(isize),
+    Acc(isize),
+    Jmp(isize),
 }
 
 impl FromStr for Instruction {
-    type Err = Error;
+    type Err = io::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_input = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
         let mut s_split = s.split_whitespace();
-        let instruction = s_split.next().unwrap();
-        let instruction_num = s_split.next().unwrap().parse::<isize>().unwrap();
+        let (instruction, argument) = match (s_split.next(), s_split.next(), s_split.next()) {
+            (Some(instruction), Some(argument), None) => (instruction, argument),
+            _ => {
+                return Err(invalid_input(format!(
+                    "{:?} does not look like \"acc +1\"",
+                    s
+                )))
+            }
+        };
+        let instruction_num = argument
+            .parse::<isize>()
+            .map_err(|e| invalid_input(format!("{:?} is not a valid number: {}", argument, e)))?;
 
         match instruction {
-            "nop" => Ok(Instruction::NOP(instruction_num)),
-            "acc" => Ok(Instruction::ACC(instruction_num)),
-            "jmp" => Ok(Instruction::JMP(instruction_num)),
-            other => Err(Error::new(
-                std::io::ErrorKind::Other,
-                format!("{:?} is not a valid instruction", other),
-            )),
+            "nop" => Ok(Instruction::Nop(instruction_num)),
+            "acc" => Ok(Instruction::Acc(instruction_num)),
+            "jmp" => Ok(Instruction::Jmp(instruction_num)),
+            other => Err(invalid_input(format!(
+                "{:?} is not a valid instruction",
+                other
+            ))),
         }
     }
 }
@@ -176,7 +216,8 @@ impl FromStr for Instruction {
 mod tests {
     use std::str::FromStr;
 
-    use super::{part2, Instruction, CPU};
+    use super::{part2, Cpu, Day8, Instruction};
+    use crate::solution::Solution;
 
     #[test]
     fn part1_test() {
@@ -195,7 +236,7 @@ acc +6";
             .map(|line| Instruction::from_str(line).unwrap())
             .collect();
 
-        let cpu = CPU::new(instructions);
+        let cpu = Cpu::new(instructions);
         let result = cpu.run();
 
         assert!(!result);
@@ -218,6 +259,19 @@ acc +6";
 
         let result = part2(&lines);
 
-        assert_eq!(result, 8);
+        assert_eq!(result, Some(8));
+    }
+
+    #[test]
+    fn bad_instructions_are_errors() {
+        assert!(Instruction::from_str("acc").is_err());
+        assert!(Instruction::from_str("acc +1 +2").is_err());
+        assert!(Instruction::from_str("acc one").is_err());
+        assert!(Instruction::from_str("mul +1").is_err());
+        assert!(Instruction::from_str("").is_err());
+
+        let lines = vec!["nop +0".to_string(), "acc".to_string()];
+        let error = <Day8 as Solution>::parse(&lines).unwrap_err();
+        assert_eq!(error.line_number, 2);
     }
 }
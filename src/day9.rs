@@ -1,131 +1,255 @@
-use std::collections::HashSet;
-
-pub fn part1(input: &[usize], preamble_size: usize) -> usize {
-    // first 25 numbers is preamble
-    // all future numbers must be a sum of two of those numbers
-    assert!(preamble_size < input.len());
-
-    let mut decryptor = XMASDecryptor::new(preamble_size);
-
-    input[0..preamble_size]
-        .iter()
-        .for_each(|v| decryptor.add_new_value(v.clone()));
-
-    input
-        .iter()
-        .skip(preamble_size)
-        .find(|v| {
-            let value = *v.clone();
-            if !decryptor.is_value_in_running_sums(value) {
-                return true;
-            }
-            decryptor.add_new_value(value);
-            false
-        })
-        .unwrap()
-        .to_owned()
-}
+use std::{
+    collections::{HashMap, VecDeque},
+    io::BufRead,
+};
 
-pub fn part2(input: &[usize], expected_sum: usize) -> usize {
-    let mut decryptor = XMASDecryptor::new(input.len());
+use crate::{
+    input_utils::{self, LineError},
+    solution::{Answer, Solution},
+};
 
-    // get where the sum is equal of a contiguous set of numbers is equal to the expected_sum
-    input
-        .iter()
-        .find(|v| {
-            let value = *v.clone();
+/// how many previous numbers each number is checked against in the real puzzle
+pub const PREAMBLE_SIZE: usize = 25;
 
-            // println!("adding value: {:?}", value);
+pub struct Day9;
 
-            decryptor.add_new_value(value);
+impl Solution for Day9 {
+    const DAY: u8 = 9;
 
-            loop {
-                // println!("running values: {:?}", decryptor.values);
-                // println!("current running sum: {:?}", decryptor.running_sum());
-                if decryptor.running_sum() == expected_sum {
-                    return true;
-                }
+    type Input = Vec<usize>;
 
-                if decryptor.running_sum() < expected_sum {
-                    break;
-                }
+    fn parse(input: &[String]) -> Result<Self::Input, LineError> {
+        input_utils::parse_all_as(input)
+    }
 
-                // println!("sum is too high! removing from decryptor");
-                decryptor.remove_from_front();
-                // println!("post-removal: {:?}", decryptor.values);
-            }
-            false
-        })
-        .unwrap();
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input, PREAMBLE_SIZE).map_or_else(
+            || Answer::from("every number is the sum of two before it"),
+            Answer::from,
+        )
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        // part 2 looks for the invalid number found in part 1
+        match part1(input, PREAMBLE_SIZE) {
+            Some(invalid_number) => part2(input, invalid_number).map_or_else(
+                || Answer::from("no contiguous set of numbers adds up to the invalid number"),
+                Answer::from,
+            ),
+            None => Answer::from("every number is the sum of two before it"),
+        }
+    }
+}
 
-    // now that decryptor has the numbers, we need to add the smallest and largest!
-    decryptor.min_value() + decryptor.max_value()
+/// The first number after the preamble that is not the sum of two of the `preamble_size`
+/// numbers before it, if there is one
+pub fn part1(input: &[usize], preamble_size: usize) -> Option<usize> {
+    XmasValidator::new(preamble_size)
+        .invalid_numbers(input.iter().copied())
+        .next()
+        .map(|invalid| invalid.value)
 }
 
-#[derive(Debug)]
-struct XMASDecryptor {
-    running_sum_length: usize,
-    all_sums: HashSet<usize>,
-    values: Vec<usize>,
+/// The smallest plus the largest number of a contiguous set of at least two numbers that adds
+/// up to `expected_sum`, if there is one
+pub fn part2(input: &[usize], expected_sum: usize) -> Option<usize> {
+    let range = find_contiguous_sum(input, expected_sum)?;
+
+    // now that we have the numbers, we need to add the smallest and largest!
+    let values = &input[range];
+    Some(values.iter().min().unwrap() + values.iter().max().unwrap())
+}
+
+/// Finds a contiguous run of at least two numbers that adds up to `expected_sum`.
+///
+/// No number is negative, so a window can slide over the input keeping a running total:
+/// grow it on the right while the total is too small, and shrink it from the left while it is
+/// too big. Each number is added and removed at most once.
+fn find_contiguous_sum(input: &[usize], expected_sum: usize) -> Option<std::ops::Range<usize>> {
+    let mut start = 0;
+    let mut running_sum = 0;
+
+    for (end, value) in input.iter().enumerate() {
+        running_sum += value;
+
+        // a single number equal to the sum is kept rather than dropped: the next number may be
+        // 0, which would make a set of two. An empty window sums to 0, so this never overruns.
+        while running_sum > expected_sum {
+            running_sum -= input[start];
+            start += 1;
+        }
+
+        if running_sum == expected_sum && end > start {
+            return Some(start..(end + 1));
+        }
+    }
+
+    None
 }
 
-impl XMASDecryptor {
-    fn new(running_sum_length: usize) -> Self {
+/// A number that is not the sum of two of the numbers before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidNumber {
+    /// 0-based position in the stream, counting the preamble
+    pub index: usize,
+    pub value: usize,
+}
+
+/// Checks an XMAS stream one number at a time. After the preamble, every number must be the
+/// sum of two different numbers among the `preamble_size` numbers just before it.
+///
+/// Only the last `preamble_size` numbers are kept, along with how many pairs of them add up to
+/// each sum, so a number is checked in constant time and accepted in `O(preamble_size)`.
+/// Invalid numbers still become part of the window for the numbers after them.
+#[derive(Debug, Clone)]
+pub struct XmasValidator {
+    preamble_size: usize,
+    sum_counts: HashMap<usize, usize>,
+    values: VecDeque<usize>,
+    next_index: usize,
+}
+
+impl XmasValidator {
+    /// `preamble_size` must be at least 1, or no number would ever leave the window
+    pub fn new(preamble_size: usize) -> Self {
+        assert!(
+            preamble_size > 0,
+            "the preamble must have at least one number"
+        );
+
         Self {
-            running_sum_length,
-            all_sums: HashSet::new(),
-            values: Vec::new(),
+            preamble_size,
+            sum_counts: HashMap::new(),
+            values: VecDeque::with_capacity(preamble_size + 1),
+            next_index: 0,
         }
     }
 
-    fn is_value_in_running_sums(&self, value: usize) -> bool {
-        self.all_sums.contains(&value)
+    pub fn preamble_size(&self) -> usize {
+        self.preamble_size
     }
 
-    fn add_new_value(&mut self, value: usize) {
-        self.values.push(value);
+    /// how many numbers have been pushed so far
+    pub fn len(&self) -> usize {
+        self.next_index
+    }
 
-        if self.values.len() > self.running_sum_length {
-            self.remove_from_front();
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+
+    /// Adds the next number in the stream, returning it if it is invalid
+    pub fn push(&mut self, value: usize) -> Option<InvalidNumber> {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let is_valid = index < self.preamble_size || self.sum_counts.contains_key(&value);
+        self.add_new_value(value);
+
+        if is_valid {
+            return None;
         }
 
-        self.recalculate_sums();
+        Some(InvalidNumber { index, value })
     }
 
-    fn min_value(&self) -> usize {
-        self.values.iter().min().unwrap().to_owned()
+    /// Lazily checks every number from `values`, yielding each invalid one
+    pub fn invalid_numbers<I>(self, values: I) -> InvalidNumbers<I::IntoIter>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        InvalidNumbers {
+            validator: self,
+            values: values.into_iter(),
+        }
     }
 
-    fn max_value(&self) -> usize {
-        self.values.iter().max().unwrap().to_owned()
+    /// Lazily checks a stream with one number per line, yielding each invalid number. Blank
+    /// lines are skipped; a line that cannot be read or parsed is yielded as an error, and
+    /// checking carries on with the next line.
+    pub fn read_invalid_numbers<R>(
+        mut self,
+        reader: R,
+    ) -> impl Iterator<Item = Result<InvalidNumber, LineError>>
+    where
+        R: BufRead,
+    {
+        reader
+            .lines()
+            .enumerate()
+            .filter_map(move |(i, line)| match line {
+                Err(e) => Some(Err(LineError::new(i, "", e))),
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => match line.trim().parse::<usize>() {
+                    Ok(value) => self.push(value).map(Ok),
+                    Err(e) => Some(Err(LineError::new(i, &line, e))),
+                },
+            })
     }
 
-    fn running_sum(&self) -> usize {
-        self.values.iter().sum()
+    fn add_new_value(&mut self, value: usize) {
+        if self.values.len() == self.preamble_size {
+            self.remove_from_front();
+        }
+
+        for v in self.values.iter() {
+            *self.sum_counts.entry(value + v).or_insert(0) += 1;
+        }
+
+        self.values.push_back(value);
     }
 
     fn remove_from_front(&mut self) {
-        self.values.remove(0);
+        let removed = match self.values.pop_front() {
+            Some(removed) => removed,
+            None => return,
+        };
+
+        for v in self.values.iter() {
+            let sum = removed + v;
+            let count = self.sum_counts.get_mut(&sum).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.sum_counts.remove(&sum);
+            }
+        }
+    }
+}
+
+/// The invalid numbers of a stream, see [`XmasValidator::invalid_numbers`]
+pub struct InvalidNumbers<I> {
+    validator: XmasValidator,
+    values: I,
+}
+
+impl<I> InvalidNumbers<I> {
+    /// the validator, holding the current window of the stream
+    pub fn validator(&self) -> &XmasValidator {
+        &self.validator
     }
+}
 
-    fn recalculate_sums(&mut self) {
-        let mut all_sums = HashSet::new();
+impl<I> Iterator for InvalidNumbers<I>
+where
+    I: Iterator<Item = usize>,
+{
+    type Item = InvalidNumber;
 
-        for i in 0..self.values.len() {
-            self.values.iter().skip(i + 1).for_each(|v| {
-                let sum = self.values[i] + v;
-                // println!("{:?} + {:?} = {:?}", self.values[i], v, sum);
-                all_sums.insert(sum);
-            })
+    fn next(&mut self) -> Option<Self::Item> {
+        for value in &mut self.values {
+            if let Some(invalid) = self.validator.push(value) {
+                return Some(invalid);
+            }
         }
 
-        self.all_sums = all_sums;
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{find_contiguous_sum, part1, part2, Day9, InvalidNumber, XmasValidator};
+    use crate::solution::{Answer, Solution};
 
     #[test]
     fn part1_test() {
@@ -157,7 +281,7 @@ mod tests {
 
         let result = part1(&lines, 5);
 
-        assert_eq!(result, 127);
+        assert_eq!(result, Some(127));
     }
 
     #[test]
@@ -190,6 +314,92 @@ mod tests {
 
         let result = part2(&lines, 127);
 
-        assert_eq!(result, 62);
+        assert_eq!(result, Some(62));
+    }
+
+    #[test]
+    fn no_answer_is_text() {
+        let short: Vec<String> = ["1", "2", "3"].iter().map(|s| s.to_string()).collect();
+        let short = <Day9 as Solution>::parse(&short).unwrap();
+        let all_valid: Vec<usize> = (1..=40).collect();
+
+        let no_invalid = Answer::from("every number is the sum of two before it");
+        for input in [short, all_valid].iter() {
+            assert_eq!(Day9::part1(input), no_invalid);
+            assert_eq!(Day9::part2(input), no_invalid);
+        }
+
+        // 1000 is invalid, and no run of the numbers before it adds up to it
+        let mut no_set: Vec<usize> = (1..=25).collect();
+        no_set.push(1000);
+        assert_eq!(
+            Day9::part2(&no_set),
+            Answer::from("no contiguous set of numbers adds up to the invalid number")
+        );
+    }
+
+    #[test]
+    fn contiguous_sum_needs_two_numbers() {
+        // 5 on its own is not a set, so the first match is 2 + 3
+        assert_eq!(find_contiguous_sum(&[1, 5, 2, 3], 5), Some(2..4));
+        assert_eq!(find_contiguous_sum(&[1, 5, 9], 5), None);
+    }
+
+    #[test]
+    fn contiguous_sum_with_zeros() {
+        assert_eq!(find_contiguous_sum(&[3, 4], 0), None);
+        assert_eq!(find_contiguous_sum(&[3, 0, 4], 0), None);
+        assert_eq!(find_contiguous_sum(&[3, 0, 0, 4], 0), Some(1..3));
+        assert_eq!(find_contiguous_sum(&[1, 5, 0, 2], 5), Some(1..3));
+    }
+
+    #[test]
+    #[should_panic(expected = "the preamble must have at least one number")]
+    fn validator_needs_a_preamble() {
+        XmasValidator::new(0);
+    }
+
+    #[test]
+    fn validator_reports_every_invalid_number() {
+        let mut validator = XmasValidator::new(3);
+
+        let invalid: Vec<InvalidNumber> = [1, 2, 3, 4, 100, 7, 104, 200, 111]
+            .iter()
+            .filter_map(|v| validator.push(*v))
+            .collect();
+
+        assert_eq!(
+            invalid,
+            vec![
+                InvalidNumber {
+                    index: 4,
+                    value: 100
+                },
+                InvalidNumber {
+                    index: 7,
+                    value: 200
+                }
+            ]
+        );
+        assert_eq!(validator.len(), 9);
+    }
+
+    #[test]
+    fn validator_reads_lines() {
+        let input = "35\n20\n15\n25\n47\n\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\nabc\n219";
+
+        let results: Vec<_> = XmasValidator::new(5)
+            .read_invalid_numbers(input.as_bytes())
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            *results[0].as_ref().unwrap(),
+            InvalidNumber {
+                index: 14,
+                value: 127
+            }
+        );
+        assert_eq!(results[1].as_ref().unwrap_err().line_number, 17);
     }
 }
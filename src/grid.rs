@@ -0,0 +1,306 @@
+use std::{convert::TryFrom, error::Error, fmt, ops::Index};
+
+use crate::input_utils::LineError;
+
+/// `(row, column)` offsets to the cells sharing an edge with a cell
+pub const DIRECTIONS_4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// `(row, column)` offsets to every cell around a cell, including diagonals
+pub const DIRECTIONS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid, stored row by row in a single `Vec`. Cells are addressed by
+/// `(row, column)`, with `(0, 0)` in the top-left corner.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// `cells` are given row by row, and there must be `width * height` of them
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height);
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses one row per line, with each character converted to a cell. Every line must be
+    /// the same length, and not empty.
+    pub fn parse(lines: &[String]) -> Result<Self, LineError>
+    where
+        T: TryFrom<char>,
+        T::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+
+        for (i, line) in lines.iter().enumerate() {
+            let length = line.chars().count();
+            if length == 0 {
+                return Err(LineError::new(i, line, "row has no cells"));
+            }
+            if length != width {
+                return Err(LineError::new(
+                    i,
+                    line,
+                    format!("row has {} cells, expected {}", length, width),
+                ));
+            }
+
+            for c in line.chars() {
+                cells.push(T::try_from(c).map_err(|e| LineError::new(i, line, e))?);
+            }
+        }
+
+        Ok(Self::new(width, lines.len(), cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        if row >= self.height || column >= self.width {
+            return None;
+        }
+
+        self.cells.get(row * self.width + column)
+    }
+
+    pub fn get_mut(&mut self, row: usize, column: usize) -> Option<&mut T> {
+        if row >= self.height || column >= self.width {
+            return None;
+        }
+
+        self.cells.get_mut(row * self.width + column)
+    }
+
+    /// Gets a cell as if the grid repeated forever in every direction
+    pub fn get_wrapping(&self, row: isize, column: isize) -> &T {
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let column = column.rem_euclid(self.width as isize) as usize;
+
+        &self.cells[row * self.width + column]
+    }
+
+    /// the position `delta` away from `(row, column)`, if it is inside the grid
+    pub fn offset(
+        &self,
+        (row, column): (usize, usize),
+        (row_delta, column_delta): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(row_delta)?;
+        let column = column.checked_add_signed(column_delta)?;
+
+        if row >= self.height || column >= self.width {
+            return None;
+        }
+
+        Some((row, column))
+    }
+
+    /// every position in the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// every cell with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// the cells sharing an edge with `(row, column)` that are inside the grid
+    pub fn neighbours4(
+        &self,
+        row: usize,
+        column: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbours_in(row, column, &DIRECTIONS_4)
+    }
+
+    /// the cells around `(row, column)`, including diagonals, that are inside the grid
+    pub fn neighbours8(
+        &self,
+        row: usize,
+        column: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbours_in(row, column, &DIRECTIONS_8)
+    }
+
+    fn neighbours_in<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        directions: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> {
+        directions.iter().filter_map(move |delta| {
+            let position = self.offset((row, column), *delta)?;
+            Some((position, &self[position]))
+        })
+    }
+
+    /// Every cell in a straight line from `(row, column)` in the given direction, not
+    /// including the starting cell, until the edge of the grid
+    pub fn ray(
+        &self,
+        row: usize,
+        column: usize,
+        direction: (isize, isize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let mut position = Some((row, column));
+
+        std::iter::from_fn(move || {
+            position = position.and_then(|p| self.offset(p, direction));
+            position.map(|p| (p, &self[p]))
+        })
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
+        self.get(row, column).unwrap_or_else(|| {
+            panic!(
+                "({}, {}) is outside a {}x{} grid",
+                row, column, self.height, self.width
+            )
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: fmt::Display> fmt::Debug for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Grid {}x{}", self.height, self.width)?;
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::Grid;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Digit(u32);
+
+    impl TryFrom<char> for Digit {
+        type Error = String;
+
+        fn try_from(c: char) -> Result<Self, Self::Error> {
+            c.to_digit(10)
+                .map(Digit)
+                .ok_or_else(|| format!("{:?} is not a digit", c))
+        }
+    }
+
+    impl std::fmt::Display for Digit {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    fn grid(input: &str) -> Grid<Digit> {
+        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+        Grid::parse(&lines).unwrap()
+    }
+
+    fn values<'a>(cells: impl Iterator<Item = ((usize, usize), &'a Digit)>) -> Vec<u32> {
+        cells.map(|(_, digit)| digit.0).collect()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = grid("123\n456");
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[(1, 0)], Digit(4));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let lines: Vec<String> = vec!["123".to_string(), "45".to_string()];
+        assert_eq!(Grid::<Digit>::parse(&lines).unwrap_err().line_number, 2);
+
+        let lines: Vec<String> = vec!["123".to_string(), "4x6".to_string()];
+        assert_eq!(Grid::<Digit>::parse(&lines).unwrap_err().line_number, 2);
+
+        // an empty grid would make get_wrapping divide by zero
+        let lines: Vec<String> = vec!["".to_string()];
+        assert_eq!(Grid::<Digit>::parse(&lines).unwrap_err().line_number, 1);
+
+        let lines: Vec<String> = vec!["123".to_string(), "".to_string()];
+        assert_eq!(Grid::<Digit>::parse(&lines).unwrap_err().line_number, 2);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = grid("123\n456\n789");
+
+        assert_eq!(values(grid.neighbours4(0, 0)), vec![2, 4]);
+        assert_eq!(values(grid.neighbours8(0, 0)), vec![2, 4, 5]);
+        assert_eq!(values(grid.neighbours8(1, 1)), vec![1, 2, 3, 4, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn rays_and_wrapping() {
+        let grid = grid("123\n456\n789");
+
+        assert_eq!(values(grid.ray(0, 0, (1, 1))), vec![5, 9]);
+        assert_eq!(values(grid.ray(2, 1, (-1, 0))), vec![5, 2]);
+        assert_eq!(values(grid.ray(0, 0, (-1, 0))), Vec::<u32>::new());
+        assert_eq!(*grid.get_wrapping(4, 7), Digit(5));
+        assert_eq!(*grid.get_wrapping(-1, -1), Digit(9));
+    }
+}
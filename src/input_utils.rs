@@ -1,19 +1,332 @@
-use std::str::FromStr;
+use std::{collections::HashMap, error::Error, fmt, io, str::FromStr};
 
-pub fn read_all(file_name: &str) -> Vec<String> {
+/// Why a puzzle input file could not be loaded
+#[derive(Debug)]
+pub enum InputError {
+    /// the file could not be read at all
+    Io { path: String, source: io::Error },
+    /// a line in the file could not be parsed
+    Parse {
+        path: String,
+        /// 1-based, to match what an editor shows
+        line_number: usize,
+        line: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io { path, source } => write!(f, "could not read {}: {}", path, source),
+            InputError::Parse {
+                path,
+                line_number,
+                line,
+                source,
+            } => write!(
+                f,
+                "{}:{}: could not parse {:?}: {}",
+                path, line_number, line, source
+            ),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Io { source, .. } => Some(source),
+            InputError::Parse { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+/// A line of already loaded input that could not be parsed. Use [`LineError::in_file`] to
+/// attach the file it came from.
+#[derive(Debug)]
+pub struct LineError {
+    /// 1-based, to match what an editor shows
+    pub line_number: usize,
+    pub line: String,
+    pub source: Box<dyn Error + Send + Sync>,
+}
+
+impl LineError {
+    pub fn new<E>(line_index: usize, line: &str, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        Self {
+            line_number: line_index + 1,
+            line: line.to_string(),
+            source: source.into(),
+        }
+    }
+
+    pub fn in_file(self, path: &str) -> InputError {
+        InputError::Parse {
+            path: path.to_string(),
+            line_number: self.line_number,
+            line: self.line,
+            source: self.source,
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: could not parse {:?}: {}",
+            self.line_number, self.line, self.source
+        )
+    }
+}
+
+impl Error for LineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+pub fn try_read_all(file_name: &str) -> Result<Vec<String>, InputError> {
     std::fs::read_to_string(file_name)
-        .expect("file not found!")
-        .lines()
-        .map(|line| line.to_string())
-        .collect()
+        .map(|contents| contents.lines().map(|line| line.to_string()).collect())
+        .map_err(|source| InputError::Io {
+            path: file_name.to_string(),
+            source,
+        })
 }
 
-pub fn read_all_as<T: FromStr>(file_name: &str) -> Vec<T> {
-    read_all(file_name)
+pub fn try_read_all_as<T>(file_name: &str) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    parse_all_as(&try_read_all(file_name)?).map_err(|e| e.in_file(file_name))
+}
+
+/// Parses every line of already loaded input, stopping at the first line that fails
+pub fn parse_all_as<T>(lines: &[String]) -> Result<Vec<T>, LineError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    lines
         .iter()
-        .map(|x| match x.parse::<T>() {
-            Ok(n) => n,
-            Err(_) => panic!("Failed to parse"),
+        .enumerate()
+        .map(|(i, line)| line.parse::<T>().map_err(|e| LineError::new(i, line, e)))
+        .collect()
+}
+
+pub fn read_all(file_name: &str) -> Vec<String> {
+    try_read_all(file_name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn read_all_as<T>(file_name: &str) -> Vec<T>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    try_read_all_as(file_name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// A run of non-blank lines, such as one passport in a batch file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Group<'a> {
+    /// 0-based index of the group's first line in the input
+    pub line_index: usize,
+    pub lines: &'a [String],
+}
+
+/// Splits input into groups separated by blank lines. Any number of blank lines can separate
+/// two groups, and blank lines at the start or end are ignored, so the last group does not need
+/// a blank line after it. A line with nothing but whitespace, like the `\r` left by CRLF line
+/// endings, counts as blank.
+pub fn groups(lines: &[String]) -> Groups<'_> {
+    Groups { lines, next: 0 }
+}
+
+/// The groups of some input, see [`groups`]
+pub struct Groups<'a> {
+    lines: &'a [String],
+    next: usize,
+}
+
+impl<'a> Iterator for Groups<'a> {
+    type Item = Group<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_blank = |line: &String| line.trim().is_empty();
+
+        let start = self.next + self.lines[self.next..].iter().position(|l| !is_blank(l))?;
+        let end = self.lines[start..]
+            .iter()
+            .position(is_blank)
+            .map_or(self.lines.len(), |length| start + length);
+        self.next = end;
+
+        Some(Group {
+            line_index: start,
+            lines: &self.lines[start..end],
+        })
+    }
+}
+
+/// What to do when a record has the same key more than once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// the record cannot be parsed
+    Reject,
+    KeepFirst,
+    KeepLast,
+}
+
+/// A group of whitespace separated `key:value` pairs, which can span several lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    /// 1-based line the record starts on, to match what an editor shows
+    pub line_number: usize,
+    fields: HashMap<String, String>,
+}
+
+impl Record {
+    pub fn parse(group: Group<'_>, duplicates: DuplicateKeys) -> Result<Self, LineError> {
+        let mut fields = HashMap::new();
+
+        for (i, line) in group.lines.iter().enumerate() {
+            let line_error = |message: String| LineError::new(group.line_index + i, line, message);
+
+            for pair in line.split_whitespace() {
+                let (key, value) = match pair.find(':') {
+                    Some(0) | None => {
+                        return Err(line_error(format!("expected key:value, got {:?}", pair)))
+                    }
+                    Some(i) => (&pair[..i], &pair[(i + 1)..]),
+                };
+
+                if fields.contains_key(key) {
+                    match duplicates {
+                        DuplicateKeys::Reject => {
+                            return Err(line_error(format!("{} is given more than once", key)))
+                        }
+                        DuplicateKeys::KeepFirst => continue,
+                        DuplicateKeys::KeepLast => {}
+                    }
+                }
+                fields.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(Self {
+            line_number: group.line_index + 1,
+            fields,
         })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|value| value.as_str())
+    }
+
+    pub fn fields(&self) -> &HashMap<String, String> {
+        &self.fields
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// Parses every blank line separated group of `key:value` pairs, stopping at the first one
+/// that fails
+pub fn parse_records(
+    lines: &[String],
+    duplicates: DuplicateKeys,
+) -> Result<Vec<Record>, LineError> {
+    groups(lines)
+        .map(|group| Record::parse(group, duplicates))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{groups, parse_all_as, parse_records, try_read_all, DuplicateKeys, InputError};
+
+    fn lines(input: &str) -> Vec<String> {
+        input.split('\n').map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_all_as_happy() {
+        let lines: Vec<String> = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+
+        assert_eq!(parse_all_as::<u32>(&lines).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn parse_all_as_reports_the_bad_line() {
+        let lines: Vec<String> = vec!["1".to_string(), "2".to_string(), "x3".to_string()];
+
+        let error = parse_all_as::<u32>(&lines).unwrap_err();
+        assert_eq!(error.line_number, 3);
+        assert_eq!(error.line, "x3");
+
+        let error = error.in_file("inputs/day1");
+        assert_eq!(
+            error.to_string(),
+            "inputs/day1:3: could not parse \"x3\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn try_read_all_missing_file() {
+        match try_read_all("inputs/does-not-exist") {
+            Err(InputError::Io { path, .. }) => assert_eq!(path, "inputs/does-not-exist"),
+            other => panic!("expected an io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn groups_handle_any_blank_lines() {
+        let input = lines("\r\na\r\nb\r\n\r\n\r\n  \nc\r\nd");
+
+        let found: Vec<(usize, Vec<&str>)> = groups(&input)
+            .map(|group| {
+                let trimmed = group.lines.iter().map(|line| line.trim()).collect();
+                (group.line_index, trimmed)
+            })
+            .collect();
+
+        assert_eq!(found, vec![(1, vec!["a", "b"]), (6, vec!["c", "d"])]);
+        assert_eq!(groups(&lines("\n\n")).count(), 0);
+        assert_eq!(groups(&[]).count(), 0);
+    }
+
+    #[test]
+    fn records() {
+        let input = lines("a:1 b:2\nc:3\n\n\nb:4 b:5\r");
+
+        let records = parse_records(&input, DuplicateKeys::KeepLast).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].line_number, 1);
+        assert_eq!(records[0].len(), 3);
+        assert_eq!(records[0].get("c"), Some("3"));
+        assert_eq!(records[1].line_number, 5);
+        assert_eq!(records[1].get("b"), Some("5"));
+
+        let records = parse_records(&input, DuplicateKeys::KeepFirst).unwrap();
+        assert_eq!(records[1].get("b"), Some("4"));
+
+        let error = parse_records(&input, DuplicateKeys::Reject).unwrap_err();
+        assert_eq!(error.line_number, 5);
+
+        let error = parse_records(&lines("a:1\nb:2 c\n"), DuplicateKeys::Reject).unwrap_err();
+        assert_eq!(error.line_number, 2);
+        assert!(parse_records(&lines(":1"), DuplicateKeys::Reject).is_err());
+    }
+}
@@ -1,4 +1,9 @@
+pub mod answers;
+pub mod grid;
 pub mod input_utils;
+pub mod schema;
+pub mod solution;
+pub mod timing;
 
 pub mod day1;
 pub mod day2;
@@ -14,3 +19,45 @@ pub mod day10;
 pub mod day11;
 pub mod day12;
 pub mod day13;
+
+use solution::Puzzle;
+
+/// Every implemented day, in calendar order
+pub static DAYS: &[&dyn Puzzle] = &[
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+];
+
+/// Looks up the solution for a day in the [`DAYS`] registry
+pub fn find_day(day: u8) -> Option<&'static dyn Puzzle> {
+    DAYS.iter().copied().find(|puzzle| puzzle.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_day, DAYS};
+
+    #[test]
+    fn registry_is_in_day_order() {
+        let days: Vec<u8> = DAYS.iter().map(|puzzle| puzzle.day()).collect();
+
+        assert!(days.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn find_day_happy() {
+        assert_eq!(find_day(11).map(|puzzle| puzzle.day()), Some(11));
+        assert!(find_day(25).is_none());
+    }
+}
@@ -1,70 +1,328 @@
-use adventofcode2020::*;
+use adventofcode2020::{
+    answers::{KnownAnswers, Verdict},
+    day2::{self, Day2},
+    day3::{self, Day3},
+    day4::{self, Day4},
+    day7::Day7,
+    find_day, input_utils,
+    schema::Schema,
+    solution::{self, Puzzle, Solution},
+    timing, DAYS,
+};
+
+mod cli;
+mod report;
+
+use cli::{AuditOptions, BagsOptions, Command, Format, PassportsOptions, PathOptions, RunOptions};
 
 fn main() {
-    let day1_input = input_utils::read_all_as::<u32>("inputs/day1");
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::Run(options) => {
+            if !run(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Time(options, format) => {
+            if !time(&options, format) {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify(options, answers_path) => {
+            if !verify(&options, &answers_path) {
+                std::process::exit(1);
+            }
+        }
+        Command::Audit(options) => {
+            if !audit(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Path(options) => {
+            if !path(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Passports(options) => {
+            if !passports(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Bags(options) => {
+            if !bags(&options) {
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// the puzzles for the selected days, or every registered day if none were selected. Days
+/// without a solution are `None`.
+fn selected_puzzles(days: &[u8]) -> Vec<(u8, Option<&'static dyn Puzzle>)> {
+    if days.is_empty() {
+        return DAYS
+            .iter()
+            .map(|puzzle| (puzzle.day(), Some(*puzzle)))
+            .collect();
+    }
+
+    days.iter().map(|day| (*day, find_day(*day))).collect()
+}
+
+/// returns false if any selected day's input could not be loaded
+fn run(options: &RunOptions) -> bool {
+    let mut failed = false;
+
+    for (day, puzzle) in selected_puzzles(&options.days) {
+        let puzzle = match puzzle {
+            Some(puzzle) => puzzle,
+            None => {
+                println!("Day {} - not implemented yet", day);
+                continue;
+            }
+        };
+
+        let input_path = options.input.clone().unwrap_or_else(|| puzzle.input_path());
+        let input = match puzzle.load(&input_path) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {} - error: {}", day, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        for part in options.parts() {
+            let answer = input.solve(part);
+            println!("Day {} - Part {}: {}", puzzle.day(), part, answer);
+        }
+    }
+
+    !failed
+}
+
+/// returns false if any selected day's input could not be loaded
+fn time(options: &RunOptions, format: Format) -> bool {
+    let mut failed = false;
+    let mut timings = Vec::new();
+
+    for (day, puzzle) in selected_puzzles(&options.days) {
+        let puzzle = match puzzle {
+            Some(puzzle) => puzzle,
+            None => {
+                eprintln!("Day {} - not implemented yet", day);
+                continue;
+            }
+        };
 
-    println!("Day 1 - Part 1: {}", day1::part1(&day1_input));
-    println!("Day 1 - Part 2: {}", day1::part2(&day1_input));
+        let input_path = options.input.clone().unwrap_or_else(|| puzzle.input_path());
+        let result = input_utils::try_read_all(&input_path).and_then(|input| {
+            timing::time_day(puzzle, &input, &options.parts()).map_err(|e| e.in_file(&input_path))
+        });
 
-    let day2_input = input_utils::read_all("inputs/day2");
+        match result {
+            Ok(day_timings) => timings.push(day_timings),
+            Err(e) => {
+                eprintln!("Day {} - error: {}", day, e);
+                failed = true;
+            }
+        }
+    }
 
-    println!("Day 2 - Part 1: {}", day2::part1(&day2_input));
-    println!("Day 2 - Part 2: {}", day2::part2(&day2_input));
+    print!("{}", report::write_timings(&timings, format));
 
-    let day3_input = input_utils::read_all("inputs/day3");
+    !failed
+}
+
+/// returns false if any answer did not match, or an input could not be loaded
+fn verify(options: &RunOptions, answers_path: &str) -> bool {
+    let known_answers = match KnownAnswers::load(answers_path) {
+        Ok(known_answers) => known_answers,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    for (day, puzzle) in selected_puzzles(&options.days) {
+        let puzzle = match puzzle {
+            Some(puzzle) => puzzle,
+            None => {
+                println!("Day {} - not implemented yet", day);
+                continue;
+            }
+        };
 
-    println!("Day 3 - Part 1: {}", day3::part1(&day3_input));
-    println!("Day 3 - Part 2: {}", day3::part2(&day3_input));
+        let input_path = options.input.clone().unwrap_or_else(|| puzzle.input_path());
+        let input = match puzzle.load(&input_path) {
+            Ok(input) => input,
+            Err(e) => {
+                println!("Day {} - error: {}", day, e);
+                failed += 1;
+                continue;
+            }
+        };
 
-    let day4_input = input_utils::read_all("inputs/day4");
+        for part in options.parts() {
+            let answer = input.solve(part);
+            let verdict = known_answers.check(day, part, &answer);
 
-    println!("Day 4 - Part 1: {}", day4::part1(&day4_input));
-    println!("Day 4 - Part 2: {}", day4::part2(&day4_input));
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Fail { .. } => failed += 1,
+                Verdict::Missing => missing += 1,
+            }
 
-    let day5_input = input_utils::read_all("inputs/day5");
+            println!("Day {} - Part {}: {} (got {})", day, part, verdict, answer);
+        }
+    }
 
-    println!("Day 5 - Part 1: {}", day5::part1(&day5_input));
-    println!("Day 5 - Part 2: {}", day5::part2(&day5_input));
+    println!(
+        "\n{} passed, {} failed, {} missing",
+        passed, failed, missing
+    );
 
-    let day6_input = input_utils::read_all("inputs/day6");
+    failed == 0
+}
+
+/// returns false if the password database could not be loaded
+fn audit(options: &AuditOptions) -> bool {
+    let input_path = options
+        .input
+        .clone()
+        .unwrap_or_else(|| solution::default_input_path(Day2::DAY));
 
-    println!("Day 6 - Part 1: {}", day6::part1(&day6_input));
-    println!("Day 6 - Part 2: {}", day6::part2(&day6_input));
+    let entries = match solution::load_input::<Day2>(&input_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
 
-    let day7_input = input_utils::read_all("inputs/day7");
+    let mut kinds = day2::AUDIT_POLICY_KINDS.to_vec();
+    if let Some(min_distinct_chars) = options.min_distinct_chars {
+        kinds.push(day2::PolicyKind::MinDistinctChars(min_distinct_chars));
+    }
 
-    println!("Day 7 - Part 1: {}", day7::part1(&day7_input));
-    println!("Day 7 - Part 2: {}", day7::part2(&day7_input));
+    print!(
+        "{}",
+        report::write_audit(&day2::audit(&entries, &kinds), options.format)
+    );
 
-    let day8_input = input_utils::read_all("inputs/day8");
+    true
+}
 
-    println!("Day 8 - Part 1: {}", day8::part1(&day8_input));
-    println!("Day 8 - Part 2: {}", day8::part2(&day8_input));
+/// returns false if the tree map could not be loaded
+fn path(options: &PathOptions) -> bool {
+    let input_path = options
+        .input
+        .clone()
+        .unwrap_or_else(|| solution::default_input_path(Day3::DAY));
 
-    let day9_input = input_utils::read_all_as::<usize>("inputs/day9");
+    let tree_map = match solution::load_input::<Day3>(&input_path) {
+        Ok(tree_map) => tree_map,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
 
-    println!("Day 9 - Part 1: {}", day9::part1(&day9_input, 25));
-    // this one is very slow, but gets the right answer. Too lazy to optimize ¯\_(ツ)_/¯
-    println!("Day 9 - Part 2 (Commented out because it is very slow). Uncomment to get answer!");
-    // println!("Day 9 - Part 2: {}", day9::part2(&day9_input, 10884537));
+    let path = day3::trace(&tree_map, options.slope);
+    let render_options = day3::RenderOptions {
+        tiles: options.tiles,
+        colour: options.colour,
+    };
 
-    let day10_input = input_utils::read_all_as::<usize>("inputs/day10");
+    print!("{}", day3::render_path(&tree_map, &path, render_options));
+    // the summary goes to stderr so the map alone can be piped somewhere
+    eprintln!("{}: {} trees hit", path.slope, path.trees);
 
-    println!("Day 10 - Part 1: {}", day10::part1(&day10_input));
-    println!("Day 10 - Part 2: {}", day10::part2(&day10_input));
+    true
+}
+
+/// returns false if the passports or the schema could not be loaded
+fn passports(options: &PassportsOptions) -> bool {
+    let input_path = options
+        .input
+        .clone()
+        .unwrap_or_else(|| solution::default_input_path(Day4::DAY));
+
+    let schema = match &options.schema {
+        Some(schema_path) => Schema::load(schema_path),
+        None => Ok(day4::passport_schema()),
+    };
+    let loaded = schema.and_then(|schema| Ok((schema, solution::load_input::<Day4>(&input_path)?)));
+
+    let (schema, input) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
+
+    if let Some(export) = options.export {
+        print!(
+            "{}",
+            report::write_passports(&day4::valid_passports(&input), export)
+        );
+        return true;
+    }
+
+    let checks = day4::check_passports(&input, &schema, options.strict);
+    print!(
+        "{}",
+        report::write_passport_report(&checks, &day4::failure_counts(&checks, &schema))
+    );
+
+    true
+}
 
-    let day11_input = input_utils::read_all("inputs/day11");
+/// returns false if the bag rules could not be loaded, the highlighted colour is not in them,
+/// or the DOT file could not be written
+fn bags(options: &BagsOptions) -> bool {
+    let input_path = options
+        .input
+        .clone()
+        .unwrap_or_else(|| solution::default_input_path(Day7::DAY));
 
-    println!("Day 11 - Part 1: {}", day11::part1(&day11_input));
-    println!("Day 11 - Part 2: {}", day11::part2(&day11_input));
+    let graph = match solution::load_input::<Day7>(&input_path) {
+        Ok(graph) => graph,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
 
-    let day12_input = input_utils::read_all("inputs/day12");
+    if let Some(colour) = &options.highlight {
+        if !graph.contains(colour) {
+            eprintln!("error: no rule mentions {} bags", colour);
+            return false;
+        }
+    }
 
-    println!("Day 12 - Part 1: {}", day12::part1(&day12_input));
-    println!("Day 12 - Part 2: {}", day12::part2(&day12_input));
+    let dot = graph.to_dot(options.highlight.as_deref());
+    if let Err(e) = std::fs::write(&options.output, dot) {
+        eprintln!("error: could not write {}: {}", options.output, e);
+        return false;
+    }
 
-    let day13_input = input_utils::read_all("inputs/day13");
+    eprintln!(
+        "wrote {} bag colours to {}",
+        graph.colours().count(),
+        options.output
+    );
 
-    println!("Day 13 - Part 1: {}", day13::part1(&day13_input));
-    // println!("Day 13 - Part 2: {}", day13::part2(&day13_input));
+    true
 }
@@ -0,0 +1,483 @@
+use std::time::Duration;
+
+use adventofcode2020::{
+    day2::AuditedEntry,
+    day4::{Passport, PassportCheck},
+    solution::Part,
+    timing::DayTimings,
+};
+
+use crate::cli::{Export, Format};
+
+pub fn write_timings(timings: &[DayTimings], format: Format) -> String {
+    match format {
+        Format::Table => timings_table(timings),
+        Format::Json => timings_json(timings),
+        Format::Csv => timings_csv(timings),
+    }
+}
+
+fn timings_table(timings: &[DayTimings]) -> String {
+    let mut table = format!(
+        "{:>3} | {:>10} | {:>10} | {:>10} | {:>10}\n",
+        "Day", "Parse", "Part 1", "Part 2", "Total"
+    );
+    table.push_str(&format!(
+        "{:-<3}-+-{:-<10}-+-{:-<10}-+-{:-<10}-+-{:-<10}\n",
+        "", "", "", "", ""
+    ));
+
+    for day in timings {
+        let part = |part| {
+            day.part(part)
+                .map_or_else(|| String::from("-"), |timed| format_duration(timed.elapsed))
+        };
+
+        table.push_str(&format!(
+            "{:>3} | {:>10} | {:>10} | {:>10} | {:>10}\n",
+            day.day,
+            format_duration(day.parse),
+            part(Part::One),
+            part(Part::Two),
+            format_duration(day.total())
+        ));
+    }
+
+    let total: Duration = timings.iter().map(|day| day.total()).sum();
+    table.push_str(&format!(
+        "{:>3} | {:>10} | {:>10} | {:>10} | {:>10}\n",
+        "",
+        "",
+        "",
+        "",
+        format_duration(total)
+    ));
+
+    table
+}
+
+fn timings_json(timings: &[DayTimings]) -> String {
+    let rows: Vec<String> = timings
+        .iter()
+        .map(|day| {
+            format!(
+                "  {{\"day\": {}, \"parse_ns\": {}, \"part1_ns\": {}, \"part2_ns\": {}, \"total_ns\": {}}}",
+                day.day,
+                day.parse.as_nanos(),
+                part_nanos(day, Part::One).unwrap_or_else(|| String::from("null")),
+                part_nanos(day, Part::Two).unwrap_or_else(|| String::from("null")),
+                day.total().as_nanos()
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+fn timings_csv(timings: &[DayTimings]) -> String {
+    let mut csv = String::from("day,parse_ns,part1_ns,part2_ns,total_ns\n");
+
+    for day in timings {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            day.day,
+            day.parse.as_nanos(),
+            part_nanos(day, Part::One).unwrap_or_default(),
+            part_nanos(day, Part::Two).unwrap_or_default(),
+            day.total().as_nanos()
+        ));
+    }
+
+    csv
+}
+
+fn part_nanos(day: &DayTimings, part: Part) -> Option<String> {
+    day.part(part)
+        .map(|timed| timed.elapsed.as_nanos().to_string())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+
+    if micros < 1_000.0 {
+        format!("{:.1}µs", micros)
+    } else if micros < 1_000_000.0 {
+        format!("{:.2}ms", micros / 1_000.0)
+    } else {
+        format!("{:.2}s", micros / 1_000_000.0)
+    }
+}
+
+pub fn write_audit(audited: &[AuditedEntry], format: Format) -> String {
+    match format {
+        Format::Table => audit_table(audited),
+        Format::Json => audit_json(audited),
+        Format::Csv => audit_csv(audited),
+    }
+}
+
+fn audit_table(audited: &[AuditedEntry]) -> String {
+    let mut table = String::new();
+
+    for audited_entry in audited {
+        table.push_str(&format!(
+            "{:>4} | {}\n",
+            audited_entry.line_number, audited_entry.entry
+        ));
+
+        for (kind, verdict) in audited_entry.verdicts.iter() {
+            let verdict = match verdict {
+                Ok(()) => String::from("valid"),
+                Err(reason) => format!("INVALID: {}", reason),
+            };
+            table.push_str(&format!("     |   {}: {}\n", kind, verdict));
+        }
+    }
+
+    table
+}
+
+fn audit_json(audited: &[AuditedEntry]) -> String {
+    let rows: Vec<String> = audited
+        .iter()
+        .map(|audited_entry| {
+            let verdicts: Vec<String> = audited_entry
+                .verdicts
+                .iter()
+                .map(|(kind, verdict)| {
+                    format!(
+                        "{}: {{\"valid\": {}, \"reason\": {}}}",
+                        json_string(&kind.to_string()),
+                        verdict.is_ok(),
+                        verdict.as_ref().err().map_or_else(
+                            || String::from("null"),
+                            |reason| json_string(&reason.to_string())
+                        )
+                    )
+                })
+                .collect();
+
+            let entry = &audited_entry.entry;
+            format!(
+                "  {{\"line\": {}, \"policy\": {}, \"password\": {}, \"verdicts\": {{{}}}}}",
+                audited_entry.line_number,
+                json_string(&entry.policy.to_string()),
+                json_string(&entry.password),
+                verdicts.join(", ")
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+fn audit_csv(audited: &[AuditedEntry]) -> String {
+    let mut header = vec![
+        String::from("line"),
+        String::from("first"),
+        String::from("second"),
+        String::from("letter"),
+        String::from("password"),
+    ];
+    if let Some(first) = audited.first() {
+        for (kind, _) in first.verdicts.iter() {
+            header.push(format!("{}_valid", kind));
+            header.push(format!("{}_reason", kind));
+        }
+    }
+
+    let mut csv = header.join(",");
+    csv.push('\n');
+
+    for audited_entry in audited {
+        let entry = &audited_entry.entry;
+        let mut row = vec![
+            audited_entry.line_number.to_string(),
+            entry.policy.first.to_string(),
+            entry.policy.second.to_string(),
+            csv_field(&entry.policy.letter.to_string()),
+            csv_field(&entry.password),
+        ];
+        for (_, verdict) in audited_entry.verdicts.iter() {
+            row.push(verdict.is_ok().to_string());
+            row.push(
+                verdict
+                    .as_ref()
+                    .err()
+                    .map_or_else(String::new, |reason| csv_field(&reason.to_string())),
+            );
+        }
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// every passport with its violations, then how many passports failed on each field
+pub fn write_passport_report(
+    checks: &[PassportCheck],
+    failure_counts: &[(String, usize)],
+) -> String {
+    let mut report = String::new();
+
+    for check in checks {
+        let verdict = if check.is_valid() { "valid" } else { "INVALID" };
+        report.push_str(&format!("{:>4} | {}\n", check.line_number, verdict));
+
+        for violation in check.violations.iter() {
+            report.push_str(&format!("     |   {}\n", violation));
+        }
+    }
+
+    let valid = checks.iter().filter(|check| check.is_valid()).count();
+    report.push_str(&format!(
+        "\n{} of {} passports valid\n",
+        valid,
+        checks.len()
+    ));
+
+    if !failure_counts.is_empty() {
+        report.push_str("\nfailures by field:\n");
+        for (key, count) in failure_counts {
+            report.push_str(&format!("  {:<6}{}\n", key, count));
+        }
+    }
+
+    report
+}
+
+/// `(line_number, passport)` pairs, one passport per line
+pub fn write_passports(passports: &[(usize, Passport)], export: Export) -> String {
+    match export {
+        Export::JsonLines => passports_json_lines(passports),
+        Export::Csv => passports_csv(passports),
+    }
+}
+
+fn passports_json_lines(passports: &[(usize, Passport)]) -> String {
+    let mut lines = String::new();
+
+    for (line_number, passport) in passports {
+        let colour = &passport.hair_colour;
+        lines.push_str(&format!(
+            "{{\"line\": {}, \"birth_year\": {}, \"issue_year\": {}, \"expiration_year\": {}, \
+             \"height\": {{\"value\": {}, \"unit\": {}}}, \
+             \"hair_colour\": {{\"red\": {}, \"green\": {}, \"blue\": {}}}, \
+             \"eye_colour\": {}, \"passport_id\": {}, \"country_id\": {}}}\n",
+            line_number,
+            passport.birth_year,
+            passport.issue_year,
+            passport.expiration_year,
+            passport.height.value,
+            json_string(&passport.height.unit.to_string()),
+            colour.red,
+            colour.green,
+            colour.blue,
+            json_string(passport.eye_colour.code()),
+            json_string(&passport.passport_id),
+            passport
+                .country_id
+                .as_ref()
+                .map_or_else(|| String::from("null"), |cid| json_string(cid)),
+        ));
+    }
+
+    lines
+}
+
+fn passports_csv(passports: &[(usize, Passport)]) -> String {
+    let mut csv = String::from(
+        "line,birth_year,issue_year,expiration_year,height_value,height_unit,hair_colour,eye_colour,passport_id,country_id\n",
+    );
+
+    for (line_number, passport) in passports {
+        let row = [
+            line_number.to_string(),
+            passport.birth_year.to_string(),
+            passport.issue_year.to_string(),
+            passport.expiration_year.to_string(),
+            passport.height.value.to_string(),
+            passport.height.unit.to_string(),
+            passport.hair_colour.to_string(),
+            passport.eye_colour.to_string(),
+            csv_field(&passport.passport_id),
+            passport
+                .country_id
+                .as_ref()
+                .map_or_else(String::new, |cid| csv_field(cid)),
+        ];
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// a JSON string literal, with quotes
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// a CSV field, quoted only when it has to be
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", s.replace('"', "\"\""));
+    }
+
+    s.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use adventofcode2020::{
+        day2::{self, PasswordEntry, PolicyKind},
+        day4::{self, PassportCheck},
+        solution::Answer,
+        timing::{DayTimings, TimedAnswer},
+    };
+
+    use super::{
+        csv_field, format_duration, json_string, write_audit, write_passport_report,
+        write_passports, write_timings,
+    };
+    use crate::cli::{Export, Format};
+
+    fn timings() -> Vec<DayTimings> {
+        vec![DayTimings {
+            day: 3,
+            parse: Duration::from_nanos(1_500),
+            part1: Some(TimedAnswer {
+                answer: Answer::Number(7),
+                elapsed: Duration::from_nanos(2_000),
+            }),
+            part2: None,
+        }]
+    }
+
+    #[test]
+    fn format_duration_picks_a_unit() {
+        assert_eq!(format_duration(Duration::from_nanos(1_500)), "1.5µs");
+        assert_eq!(format_duration(Duration::from_micros(2_345)), "2.35ms");
+        assert_eq!(format_duration(Duration::from_millis(3_210)), "3.21s");
+    }
+
+    #[test]
+    fn timings_csv() {
+        assert_eq!(
+            write_timings(&timings(), Format::Csv),
+            "day,parse_ns,part1_ns,part2_ns,total_ns\n3,1500,2000,,3500\n"
+        );
+    }
+
+    #[test]
+    fn timings_json() {
+        assert_eq!(
+            write_timings(&timings(), Format::Json),
+            "[\n  {\"day\": 3, \"parse_ns\": 1500, \"part1_ns\": 2000, \"part2_ns\": null, \"total_ns\": 3500}\n]\n"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\n\"");
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn audit_formats() {
+        let entries = vec!["1-3 b: cdefg".parse::<PasswordEntry>().unwrap()];
+        let audited = day2::audit(
+            &entries,
+            &[PolicyKind::CountRange, PolicyKind::NoneOfPositions],
+        );
+
+        assert_eq!(
+            write_audit(&audited, Format::Csv),
+            "line,first,second,letter,password,count-range_valid,count-range_reason,none-of-positions_valid,none-of-positions_reason
+1,1,3,b,cdefg,false,\"letter appears 0 times, expected 1 to 3\",true,
+"
+        );
+        assert_eq!(
+            write_audit(&audited, Format::Json),
+            "[
+  {\"line\": 1, \"policy\": \"1-3 b\", \"password\": \"cdefg\", \"verdicts\": {\"count-range\": {\"valid\": false, \"reason\": \"letter appears 0 times, expected 1 to 3\"}, \"none-of-positions\": {\"valid\": true, \"reason\": null}}}
+]
+"
+        );
+    }
+
+    #[test]
+    fn passport_report() {
+        let lines: Vec<String> = "byr:1919\n\nbyr:1920"
+            .lines()
+            .map(|line| line.to_string())
+            .collect();
+        let schema = adventofcode2020::schema::Schema::new().field(
+            "byr",
+            true,
+            adventofcode2020::schema::Rule::IntRange(1920..=2002),
+        );
+        let passports = day4::parse_passports(&lines).unwrap();
+        let checks: Vec<PassportCheck> = day4::check_passports(&passports, &schema, true);
+
+        assert_eq!(
+            write_passport_report(&checks, &day4::failure_counts(&checks, &schema)),
+            "   1 | INVALID
+     |   byr: out of range (1919 < 1920)
+   3 | valid
+
+1 of 2 passports valid
+
+failures by field:
+  byr   1
+"
+        );
+    }
+
+    #[test]
+    fn passport_exports() {
+        let lines: Vec<String> = "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry
+pid:060033327 cid:147
+
+hgt:59in byr:1931 iyr:2013 eyr:2024 hcl:#ae17e1 ecl:brn pid:760753108"
+            .lines()
+            .map(|line| line.to_string())
+            .collect();
+        let passports = day4::valid_passports(&day4::parse_passports(&lines).unwrap());
+
+        assert_eq!(
+            write_passports(&passports, Export::JsonLines),
+            "{\"line\": 1, \"birth_year\": 1937, \"issue_year\": 2017, \"expiration_year\": 2020, \"height\": {\"value\": 183, \"unit\": \"cm\"}, \"hair_colour\": {\"red\": 255, \"green\": 255, \"blue\": 253}, \"eye_colour\": \"gry\", \"passport_id\": \"060033327\", \"country_id\": \"147\"}
+{\"line\": 4, \"birth_year\": 1931, \"issue_year\": 2013, \"expiration_year\": 2024, \"height\": {\"value\": 59, \"unit\": \"in\"}, \"hair_colour\": {\"red\": 174, \"green\": 23, \"blue\": 225}, \"eye_colour\": \"brn\", \"passport_id\": \"760753108\", \"country_id\": null}
+"
+        );
+        assert_eq!(
+            write_passports(&passports, Export::Csv),
+            "line,birth_year,issue_year,expiration_year,height_value,height_unit,hair_colour,eye_colour,passport_id,country_id
+1,1937,2017,2020,183,cm,#fffffd,gry,060033327,147
+4,1931,2013,2024,59,in,#ae17e1,brn,760753108,
+"
+        );
+    }
+}
@@ -0,0 +1,679 @@
+use std::{collections::HashMap, fmt, ops::RangeInclusive};
+
+use regex::Regex;
+
+use crate::input_utils::{self, InputError, LineError};
+
+/// What a field's value must look like
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// any value is accepted, only whether the field is there matters
+    Any,
+    /// a whole number in the range
+    IntRange(RangeInclusive<u64>),
+    /// exactly this many digits, keeping any leading zeroes
+    Digits(usize),
+    /// a whole number followed by one of the units, in the range for that unit
+    UnitRange(Vec<(String, RangeInclusive<u64>)>),
+    /// matches the regex. Add `^` and `$` to match the whole value.
+    Regex(Regex),
+    /// exactly one of the values
+    OneOf(Vec<String>),
+}
+
+impl Rule {
+    pub fn matches(&self, value: &str) -> bool {
+        self.check(value).is_ok()
+    }
+
+    /// why `value` does not follow the rule, if it doesn't
+    pub fn check(&self, value: &str) -> Result<(), Violation> {
+        match self {
+            Rule::Any => Ok(()),
+            Rule::IntRange(range) => {
+                let number = parse_number(value).ok_or_else(|| Violation::NotANumber {
+                    value: value.to_string(),
+                })?;
+                check_range(number, range, "")
+            }
+            Rule::Digits(length) => {
+                // only the digits matter, so a value too long for a u64 is just the wrong length
+                if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(Violation::NotANumber {
+                        value: value.to_string(),
+                    });
+                }
+                if value.len() != *length {
+                    return Err(Violation::WrongLength {
+                        length: value.len(),
+                        expected: *length,
+                    });
+                }
+                Ok(())
+            }
+            Rule::UnitRange(units) => {
+                let (number, unit, range) = units
+                    .iter()
+                    .find_map(|(unit, range)| {
+                        let number = parse_number(value.strip_suffix(unit.as_str())?)?;
+                        Some((number, unit, range))
+                    })
+                    .ok_or_else(|| Violation::BadUnit {
+                        value: value.to_string(),
+                        units: units.iter().map(|(unit, _)| unit.clone()).collect(),
+                    })?;
+                check_range(number, range, unit)
+            }
+            Rule::Regex(regex) if regex.is_match(value) => Ok(()),
+            Rule::Regex(regex) => Err(Violation::NoMatch {
+                value: value.to_string(),
+                pattern: regex.as_str().to_string(),
+            }),
+            Rule::OneOf(values) if values.iter().any(|v| v == value) => Ok(()),
+            Rule::OneOf(values) => Err(Violation::NotOneOf {
+                value: value.to_string(),
+                expected: values.clone(),
+            }),
+        }
+    }
+}
+
+fn check_range(number: u64, range: &RangeInclusive<u64>, unit: &str) -> Result<(), Violation> {
+    if range.contains(&number) {
+        return Ok(());
+    }
+
+    Err(Violation::OutOfRange {
+        value: number,
+        range: range.clone(),
+        unit: unit.to_string(),
+    })
+}
+
+/// Why a field was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// a required field is not there
+    Missing,
+    NotANumber {
+        value: String,
+    },
+    OutOfRange {
+        value: u64,
+        range: RangeInclusive<u64>,
+        /// empty for plain numbers
+        unit: String,
+    },
+    /// not a number followed by one of the units
+    BadUnit {
+        value: String,
+        units: Vec<String>,
+    },
+    /// how many digits the value has
+    WrongLength {
+        length: usize,
+        expected: usize,
+    },
+    NoMatch {
+        value: String,
+        pattern: String,
+    },
+    NotOneOf {
+        value: String,
+        expected: Vec<String>,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Missing => f.write_str("missing"),
+            Violation::NotANumber { value } => write!(f, "{:?} is not a number", value),
+            Violation::OutOfRange { value, range, unit } if value < range.start() => write!(
+                f,
+                "out of range ({}{} < {}{})",
+                value,
+                unit,
+                range.start(),
+                unit
+            ),
+            Violation::OutOfRange { value, range, unit } => write!(
+                f,
+                "out of range ({}{} > {}{})",
+                value,
+                unit,
+                range.end(),
+                unit
+            ),
+            Violation::BadUnit { value, units } => write!(
+                f,
+                "bad unit in {:?}, expected a number followed by {}",
+                value,
+                units.join(" or ")
+            ),
+            Violation::WrongLength { length, expected } => {
+                write!(f, "length {}, expected {}", length, expected)
+            }
+            Violation::NoMatch { value, pattern } => {
+                write!(f, "{:?} does not match {}", value, pattern)
+            }
+            Violation::NotOneOf { value, expected } => {
+                write!(f, "{:?} is not one of {}", value, expected.join(", "))
+            }
+        }
+    }
+}
+
+/// A field that was rejected, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldViolation {
+    pub key: String,
+    pub violation: Violation,
+}
+
+impl fmt::Display for FieldViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.violation)
+    }
+}
+
+impl PartialEq for Rule {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Rule::Any, Rule::Any) => true,
+            (Rule::IntRange(a), Rule::IntRange(b)) => a == b,
+            (Rule::Digits(a), Rule::Digits(b)) => a == b,
+            (Rule::UnitRange(a), Rule::UnitRange(b)) => a == b,
+            (Rule::Regex(a), Rule::Regex(b)) => a.as_str() == b.as_str(),
+            (Rule::OneOf(a), Rule::OneOf(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Any => f.write_str("any value"),
+            Rule::IntRange(range) => {
+                write!(f, "a number from {} to {}", range.start(), range.end())
+            }
+            Rule::Digits(length) => write!(f, "{} digits", length),
+            Rule::UnitRange(units) => {
+                let units: Vec<String> = units
+                    .iter()
+                    .map(|(unit, range)| format!("{}-{}{}", range.start(), range.end(), unit))
+                    .collect();
+                f.write_str(&units.join(" or "))
+            }
+            Rule::Regex(regex) => write!(f, "matching {}", regex.as_str()),
+            Rule::OneOf(values) => write!(f, "one of {}", values.join(", ")),
+        }
+    }
+}
+
+/// only plain digits, so signs and spaces are not accepted
+fn parse_number(value: &str) -> Option<u64> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    value.parse::<u64>().ok()
+}
+
+/// A field a document can have
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub key: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+/// The fields a document of `key:value` pairs should have, and the rules their values must
+/// follow. Fields that are not in the schema are ignored.
+///
+/// A schema can be built in code with [`Schema::field`], or loaded from a small subset of TOML
+/// with one table per field, in the order the fields should be checked:
+///
+/// ```toml
+/// [byr]
+/// required = true
+/// int = "1920-2002"
+///
+/// [hgt]
+/// required = true
+/// unit = "cm 150-193, in 59-76"
+///
+/// [hcl]
+/// regex = "^#[0-9a-f]{6}$"
+///
+/// [ecl]
+/// enum = "amb blu brn"
+///
+/// [pid]
+/// digits = "9"
+/// ```
+///
+/// `required` defaults to false, and a field without a rule accepts any value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field to the end of the schema. Panics if the field is already there.
+    pub fn field(mut self, key: &str, required: bool, rule: Rule) -> Self {
+        assert!(self.get(key).is_none(), "{} is already in the schema", key);
+
+        self.fields.push(Field {
+            key: key.to_string(),
+            required,
+            rule,
+        });
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    pub fn load(file_name: &str) -> Result<Self, InputError> {
+        Self::parse(&input_utils::try_read_all(file_name)?).map_err(|e| e.in_file(file_name))
+    }
+
+    pub fn parse(lines: &[String]) -> Result<Self, LineError> {
+        let mut schema = Schema::new();
+        let mut current: Option<(Field, bool)> = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_error = |message: String| LineError::new(i, line, message);
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = trimmed.strip_prefix('[') {
+                let key = table
+                    .strip_suffix(']')
+                    .map(|key| key.trim())
+                    .filter(|key| !key.is_empty())
+                    .ok_or_else(|| line_error(String::from("expected a table like [byr]")))?;
+                if let Some((field, _)) = current.take() {
+                    schema.fields.push(field);
+                }
+                if schema.get(key).is_some() {
+                    return Err(line_error(format!("{} is already defined", key)));
+                }
+
+                current = Some((
+                    Field {
+                        key: key.to_string(),
+                        required: false,
+                        rule: Rule::Any,
+                    },
+                    false,
+                ));
+                continue;
+            }
+
+            let (field, has_rule) = current
+                .as_mut()
+                .ok_or_else(|| line_error(String::from("setting is not inside a [field] table")))?;
+
+            let (key, value) = match trimmed.find('=') {
+                Some(i) => (trimmed[..i].trim(), trimmed[(i + 1)..].trim()),
+                None => return Err(line_error(String::from("expected setting = value"))),
+            };
+
+            if key == "required" {
+                field.required = match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(line_error(String::from("required must be true or false"))),
+                };
+                continue;
+            }
+
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .ok_or_else(|| line_error(format!("{} must be a quoted string", key)))?;
+
+            if *has_rule {
+                return Err(line_error(format!("{} already has a rule", field.key)));
+            }
+            field.rule = parse_rule(key, value).map_err(line_error)?;
+            *has_rule = true;
+        }
+
+        if let Some((field, _)) = current {
+            schema.fields.push(field);
+        }
+
+        Ok(schema)
+    }
+
+    /// whether every required field is there, whatever its value
+    pub fn has_required_fields(&self, document: &HashMap<String, String>) -> bool {
+        self.missing_fields(document).is_empty()
+    }
+
+    /// whether every required field is there, and every field in the schema follows its rule
+    pub fn is_valid(&self, document: &HashMap<String, String>) -> bool {
+        self.validate(document).is_empty()
+    }
+
+    /// every required field that is not there, in schema order
+    pub fn missing_fields(&self, document: &HashMap<String, String>) -> Vec<FieldViolation> {
+        self.fields
+            .iter()
+            .filter(|field| field.required && !document.contains_key(&field.key))
+            .map(|field| FieldViolation {
+                key: field.key.clone(),
+                violation: Violation::Missing,
+            })
+            .collect()
+    }
+
+    /// every field that is missing or breaks its rule, in schema order
+    pub fn validate(&self, document: &HashMap<String, String>) -> Vec<FieldViolation> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let violation = match document.get(&field.key) {
+                    Some(value) => field.rule.check(value).err()?,
+                    None if field.required => Violation::Missing,
+                    None => return None,
+                };
+
+                Some(FieldViolation {
+                    key: field.key.clone(),
+                    violation,
+                })
+            })
+            .collect()
+    }
+}
+
+fn parse_rule(kind: &str, value: &str) -> Result<Rule, String> {
+    match kind {
+        "int" => parse_range(value).map(Rule::IntRange),
+        "digits" => match parse_number(value) {
+            Some(length) if length > 0 => Ok(Rule::Digits(length as usize)),
+            _ => Err(format!("invalid digits {:?}, expected a length", value)),
+        },
+        "unit" => value
+            .split(',')
+            .map(|unit_range| {
+                let mut parts = unit_range.split_whitespace();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(unit), Some(range), None) => Ok((unit.to_string(), parse_range(range)?)),
+                    _ => Err(format!(
+                        "expected units like \"cm 150-193, in 59-76\", got {:?}",
+                        value
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Rule::UnitRange),
+        "regex" => Regex::new(value)
+            .map(Rule::Regex)
+            .map_err(|e| e.to_string()),
+        "enum" => {
+            let values: Vec<String> = value.split_whitespace().map(|v| v.to_string()).collect();
+            if values.is_empty() {
+                return Err(String::from("enum needs at least one value"));
+            }
+            Ok(Rule::OneOf(values))
+        }
+        other => Err(format!(
+            "unknown setting {:?}, expected required, int, digits, unit, regex or enum",
+            other
+        )),
+    }
+}
+
+fn parse_range(s: &str) -> Result<RangeInclusive<u64>, String> {
+    let range = s
+        .find('-')
+        .and_then(|i| Some((parse_number(&s[..i])?, parse_number(&s[(i + 1)..])?)));
+
+    match range {
+        Some((start, end)) if start <= end => Ok(start..=end),
+        _ => Err(format!(
+            "invalid range {:?}, expected something like 10-20",
+            s
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use regex::Regex;
+
+    use super::{FieldViolation, Rule, Schema, Violation};
+
+    fn parse(input: &str) -> Result<Schema, super::LineError> {
+        let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+        Schema::parse(&lines)
+    }
+
+    fn document(input: &str) -> HashMap<String, String> {
+        input
+            .split_whitespace()
+            .map(|pair| {
+                let (key, value) = pair.split_at(pair.find(':').unwrap());
+                (key.to_string(), value[1..].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rules() {
+        let year = Rule::IntRange(1920..=2002);
+        assert!(year.matches("2002"));
+        assert!(!year.matches("2003"));
+        assert!(!year.matches("abc"));
+        assert!(!year.matches("+1990"));
+
+        let height = Rule::UnitRange(vec![
+            (String::from("cm"), 150..=193),
+            (String::from("in"), 59..=76),
+        ]);
+        assert!(height.matches("60in"));
+        assert!(height.matches("190cm"));
+        assert!(!height.matches("190in"));
+        assert!(!height.matches("190"));
+        assert!(!height.matches("cm"));
+
+        let colour = Rule::Regex(Regex::new("^#[0-9a-f]{6}$").unwrap());
+        assert!(colour.matches("#123abc"));
+        assert!(!colour.matches("#123abz"));
+
+        let eyes = Rule::OneOf(vec![String::from("brn"), String::from("hzl")]);
+        assert!(eyes.matches("brn"));
+        assert!(!eyes.matches("wat"));
+    }
+
+    #[test]
+    fn parse_matches_the_builder() {
+        let schema = parse(
+            "# a tiny schema
+[byr]
+required = true
+int = \"1920-2002\"
+
+[hgt]
+required = true
+unit = \"cm 150-193, in 59-76\"
+
+[ecl]
+enum = \"brn hzl\"
+
+[pid]
+digits = \"9\"
+
+[cid]",
+        )
+        .unwrap();
+
+        let expected = Schema::new()
+            .field("byr", true, Rule::IntRange(1920..=2002))
+            .field(
+                "hgt",
+                true,
+                Rule::UnitRange(vec![
+                    (String::from("cm"), 150..=193),
+                    (String::from("in"), 59..=76),
+                ]),
+            )
+            .field(
+                "ecl",
+                false,
+                Rule::OneOf(vec![String::from("brn"), String::from("hzl")]),
+            )
+            .field("pid", false, Rule::Digits(9))
+            .field("cid", false, Rule::Any);
+
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (input, line_number) in [
+            ("required = true", 1),
+            ("[byr]\nrequired = yes", 2),
+            ("[byr]\nint = 1920-2002", 2),
+            ("[byr]\nint = \"2002-1920\"", 2),
+            ("[byr]\nint = \"1-2\"\nenum = \"a\"", 3),
+            ("[hgt]\nunit = \"cm\"", 2),
+            ("[hcl]\nregex = \"(\"", 2),
+            ("[byr]\n[byr]", 2),
+            ("[byr]\nsize = \"1\"", 2),
+            ("[pid]\ndigits = \"0\"", 2),
+        ] {
+            assert_eq!(
+                parse(input).unwrap_err().line_number,
+                line_number,
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn validate_documents() {
+        let schema = Schema::new()
+            .field("byr", true, Rule::IntRange(1920..=2002))
+            .field("cid", false, Rule::IntRange(1..=10));
+
+        assert!(schema.is_valid(&document("byr:1920 other:x")));
+        assert!(!schema.is_valid(&document("byr:1919")));
+        assert!(!schema.is_valid(&document("byr:1920 cid:11")));
+
+        assert!(schema.has_required_fields(&document("byr:abc")));
+        assert!(!schema.has_required_fields(&document("cid:3")));
+    }
+
+    #[test]
+    fn violations_explain_the_failure() {
+        let check = |rule: Rule, value: &str| rule.check(value).unwrap_err().to_string();
+
+        assert_eq!(
+            check(Rule::IntRange(1920..=2002), "1919"),
+            "out of range (1919 < 1920)"
+        );
+        assert_eq!(
+            check(Rule::IntRange(1920..=2002), "2003"),
+            "out of range (2003 > 2002)"
+        );
+        assert_eq!(
+            check(
+                Rule::UnitRange(vec![(String::from("cm"), 150..=193)]),
+                "194cm"
+            ),
+            "out of range (194cm > 193cm)"
+        );
+        assert_eq!(
+            check(
+                Rule::UnitRange(vec![
+                    (String::from("cm"), 150..=193),
+                    (String::from("in"), 59..=76),
+                ]),
+                "190"
+            ),
+            "bad unit in \"190\", expected a number followed by cm or in"
+        );
+        assert_eq!(
+            check(Rule::Digits(9), "0123456789"),
+            "length 10, expected 9"
+        );
+        assert_eq!(
+            check(Rule::Digits(9), "0123456789012345678901234"),
+            "length 25, expected 9"
+        );
+        assert_eq!(
+            check(Rule::Digits(9), "01234567a"),
+            "\"01234567a\" is not a number"
+        );
+        assert_eq!(
+            check(Rule::Regex(Regex::new("^#[0-9a-f]{6}$").unwrap()), "123abc"),
+            "\"123abc\" does not match ^#[0-9a-f]{6}$"
+        );
+        assert_eq!(
+            check(Rule::OneOf(vec![String::from("brn")]), "wat"),
+            "\"wat\" is not one of brn"
+        );
+    }
+
+    #[test]
+    fn validate_lists_every_violation() {
+        let schema = Schema::new()
+            .field("byr", true, Rule::IntRange(1920..=2002))
+            .field("hgt", true, Rule::Any)
+            .field("pid", true, Rule::Digits(9));
+
+        assert_eq!(
+            schema.validate(&document("pid:12 byr:1919")),
+            vec![
+                FieldViolation {
+                    key: String::from("byr"),
+                    violation: Violation::OutOfRange {
+                        value: 1919,
+                        range: 1920..=2002,
+                        unit: String::new(),
+                    },
+                },
+                FieldViolation {
+                    key: String::from("hgt"),
+                    violation: Violation::Missing,
+                },
+                FieldViolation {
+                    key: String::from("pid"),
+                    violation: Violation::WrongLength {
+                        length: 2,
+                        expected: 9,
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            schema.missing_fields(&document("pid:12 byr:1919")),
+            vec![FieldViolation {
+                key: String::from("hgt"),
+                violation: Violation::Missing,
+            }]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+
+use crate::input_utils::{self, InputError, LineError};
+
+/// The answer to one part of a day's puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// the part has not been solved yet
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => f.write_str(s),
+            Answer::Unsolved => f.write_str("not implemented yet"),
+        }
+    }
+}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Number(n as i64)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_number!(u32, u64, usize, i32, i64, isize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+/// One of the two parts of each day's puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => f.write_str("1"),
+            Part::Two => f.write_str("2"),
+        }
+    }
+}
+
+/// A single day's puzzle. The input is parsed once and then shared by both parts.
+pub trait Solution {
+    /// the day of the advent calendar this solves
+    const DAY: u8;
+
+    type Input;
+
+    fn parse(input: &[String]) -> Result<Self::Input, LineError>;
+
+    fn part1(input: &Self::Input) -> Answer;
+
+    fn part2(input: &Self::Input) -> Answer;
+}
+
+/// where a day's puzzle input is checked in, relative to the repository root
+pub fn default_input_path(day: u8) -> String {
+    format!("inputs/day{}", day)
+}
+
+/// Reads and parses a day's puzzle input at `path`, keeping its concrete input type
+pub fn load_input<S: Solution>(path: &str) -> Result<S::Input, InputError> {
+    S::parse(&input_utils::try_read_all(path)?).map_err(|e| e.in_file(path))
+}
+
+/// An object safe view of a [`Solution`], so that days with different input types can be kept
+/// together in the [registry](crate::DAYS).
+pub trait Puzzle: Sync {
+    fn day(&self) -> u8;
+
+    fn parse(&self, input: &[String]) -> Result<Box<dyn ParsedInput>, LineError>;
+
+    /// where the puzzle input is checked in, relative to the repository root
+    fn input_path(&self) -> String {
+        default_input_path(self.day())
+    }
+
+    /// Reads and parses the puzzle input at `path`
+    fn load(&self, path: &str) -> Result<Box<dyn ParsedInput>, InputError> {
+        self.parse(&input_utils::try_read_all(path)?)
+            .map_err(|e| e.in_file(path))
+    }
+}
+
+/// A day's parsed input, ready to solve either part
+pub trait ParsedInput {
+    fn part1(&self) -> Answer;
+
+    fn part2(&self) -> Answer;
+
+    fn solve(&self, part: Part) -> Answer {
+        match part {
+            Part::One => self.part1(),
+            Part::Two => self.part2(),
+        }
+    }
+}
+
+struct Parsed<S: Solution> {
+    input: S::Input,
+}
+
+impl<S: Solution> ParsedInput for Parsed<S> {
+    fn part1(&self) -> Answer {
+        S::part1(&self.input)
+    }
+
+    fn part2(&self) -> Answer {
+        S::part2(&self.input)
+    }
+}
+
+impl<S> Puzzle for S
+where
+    S: Solution + Sync + 'static,
+    S::Input: 'static,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &[String]) -> Result<Box<dyn ParsedInput>, LineError> {
+        let input = S::parse(input)?;
+        Ok(Box::new(Parsed::<S> { input }))
+    }
+}
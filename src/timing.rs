@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    input_utils::LineError,
+    solution::{Answer, Part, Puzzle},
+};
+
+/// How long one day took to parse its input and to solve each part
+#[derive(Debug, Clone)]
+pub struct DayTimings {
+    pub day: u8,
+    pub parse: Duration,
+    /// `None` when the part was not run
+    pub part1: Option<TimedAnswer>,
+    pub part2: Option<TimedAnswer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimedAnswer {
+    pub answer: Answer,
+    pub elapsed: Duration,
+}
+
+impl DayTimings {
+    pub fn part(&self, part: Part) -> Option<&TimedAnswer> {
+        match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse
+            + [&self.part1, &self.part2]
+                .iter()
+                .filter_map(|timed| timed.as_ref())
+                .map(|timed| timed.elapsed)
+                .sum::<Duration>()
+    }
+}
+
+/// Parses `input` and solves the given parts, timing each step separately. Reading the input
+/// file is not included.
+pub fn time_day(
+    puzzle: &dyn Puzzle,
+    input: &[String],
+    parts: &[Part],
+) -> Result<DayTimings, LineError> {
+    let start = Instant::now();
+    let parsed = puzzle.parse(input)?;
+    let parse = start.elapsed();
+
+    let solve = |part: Part| {
+        if !parts.contains(&part) {
+            return None;
+        }
+
+        let start = Instant::now();
+        let answer = parsed.solve(part);
+        Some(TimedAnswer {
+            answer,
+            elapsed: start.elapsed(),
+        })
+    };
+
+    Ok(DayTimings {
+        day: puzzle.day(),
+        parse,
+        part1: solve(Part::One),
+        part2: solve(Part::Two),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::time_day;
+    use crate::{day6::Day6, solution::Part};
+
+    #[test]
+    fn time_day_only_runs_selected_parts() {
+        let input: Vec<String> = vec!["ab".to_string(), "ac".to_string()];
+
+        let timings = time_day(&Day6, &input, &[Part::Two]).unwrap();
+
+        assert_eq!(timings.day, 6);
+        assert!(timings.part1.is_none());
+        assert_eq!(timings.part2.unwrap().answer.to_string(), "1");
+    }
+}